@@ -90,3 +90,40 @@ pub fn debug_marker(context: &ComPtr<d3d11::ID3D11DeviceContext>, args: fmt::Arg
         annotation.SetMarker(msg.as_ptr() as _);
     }
 }
+
+// Annotations for the user facing debug markers, which can span several
+// commands and so can't be scoped.
+#[cfg(debug_assertions)]
+fn user_annotation(
+    context: &ComPtr<d3d11::ID3D11DeviceContext>,
+) -> Option<ComPtr<temp::ID3DUserDefinedAnnotation>> {
+    // same here
+    if unsafe { context.GetType() } == d3d11::D3D11_DEVICE_CONTEXT_DEFERRED {
+        if env::var("GFX_NO_RENDERDOC").is_ok() {
+            return None;
+        }
+    }
+
+    context.cast::<temp::ID3DUserDefinedAnnotation>().ok()
+}
+
+#[cfg(debug_assertions)]
+pub fn begin_event(context: &ComPtr<d3d11::ID3D11DeviceContext>, name: &str) {
+    if let Some(annotation) = user_annotation(context) {
+        let msg: &OsStr = name.as_ref();
+        let msg: Vec<u16> = msg.to_wide_null();
+
+        unsafe {
+            annotation.BeginEvent(msg.as_ptr() as _);
+        }
+    }
+}
+
+#[cfg(debug_assertions)]
+pub fn end_event(context: &ComPtr<d3d11::ID3D11DeviceContext>) {
+    if let Some(annotation) = user_annotation(context) {
+        unsafe {
+            annotation.EndEvent();
+        }
+    }
+}
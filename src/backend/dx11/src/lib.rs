@@ -2187,6 +2187,25 @@ impl hal::command::RawCommandBuffer<Backend> for CommandBuffer {
     {
         unimplemented!()
     }
+
+    unsafe fn begin_debug_marker(&mut self, name: &str, _color: u32) {
+        // Annotations are only issued in debug builds, and carry no color.
+        #[cfg(debug_assertions)]
+        debug::begin_event(&self.context, name);
+        #[cfg(not(debug_assertions))]
+        let _ = name;
+    }
+
+    unsafe fn end_debug_marker(&mut self) {
+        #[cfg(debug_assertions)]
+        debug::end_event(&self.context);
+    }
+
+    unsafe fn insert_debug_marker(&mut self, name: &str, _color: u32) {
+        debug_marker!(&self.context, "{}", name);
+        #[cfg(not(debug_assertions))]
+        let _ = name;
+    }
 }
 
 bitflags! {
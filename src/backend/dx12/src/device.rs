@@ -1652,7 +1652,13 @@ impl d::Device<B> for Device {
                 },
                 NumElements: input_element_descs.len() as u32,
             },
-            IBStripCutValue: d3d12::D3D12_INDEX_BUFFER_STRIP_CUT_VALUE_DISABLED, // TODO
+            IBStripCutValue: match desc.input_assembler.primitive_restart {
+                pso::PrimitiveRestart::Disabled => {
+                    d3d12::D3D12_INDEX_BUFFER_STRIP_CUT_VALUE_DISABLED
+                }
+                pso::PrimitiveRestart::U16 => d3d12::D3D12_INDEX_BUFFER_STRIP_CUT_VALUE_0xFFFF,
+                pso::PrimitiveRestart::U32 => d3d12::D3D12_INDEX_BUFFER_STRIP_CUT_VALUE_0xFFFFFFFF,
+            },
             PrimitiveTopologyType: conv::map_topology_type(desc.input_assembler.primitive),
             NumRenderTargets: num_rtvs,
             RTVFormats: rtvs,
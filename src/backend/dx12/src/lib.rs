@@ -1034,6 +1034,31 @@ impl hal::Instance for Instance {
                 }
             };
 
+            // Timestamps count ticks of the queue they are written on, all our
+            // queues report the frequency of a direct queue.
+            let timestamp_period = {
+                let (queue, hr_queue) = device.create_command_queue(
+                    native::command_list::CmdListType::Direct,
+                    native::queue::Priority::Normal,
+                    native::queue::CommandQueueFlags::empty(),
+                    0,
+                );
+                let mut frequency = 0u64;
+                if winerror::SUCCEEDED(hr_queue) {
+                    unsafe {
+                        if !winerror::SUCCEEDED(queue.GetTimestampFrequency(&mut frequency)) {
+                            frequency = 0;
+                        }
+                        queue.destroy();
+                    }
+                }
+                if frequency != 0 {
+                    1_000_000_000.0 / frequency as f32
+                } else {
+                    0.0
+                }
+            };
+
             let physical_device = PhysicalDevice {
                 adapter,
                 features:
@@ -1096,6 +1121,7 @@ impl hal::Instance for Instance {
                     optimal_buffer_copy_offset_alignment: d3d12::D3D12_TEXTURE_DATA_PLACEMENT_ALIGNMENT as _,
                     optimal_buffer_copy_pitch_alignment: d3d12::D3D12_TEXTURE_DATA_PITCH_ALIGNMENT as _,
                     min_vertex_input_binding_stride_alignment: 1,
+                    timestamp_period,
                     .. Limits::default() //TODO
                 },
                 format_properties: Arc::new(FormatProperties::new(device)),
@@ -2,17 +2,17 @@
 
 use crate::gl;
 
-use crate::hal::format::ChannelType;
+use crate::hal::format::{self, ChannelType};
 use crate::hal::range::RangeArg;
 use crate::hal::{self, buffer, command, image, memory, pass, pso, query, ColorSlot};
 
 use crate::pool::{self, BufferMemory};
-use crate::{native as n, Backend};
+use crate::{conv, native as n, Backend};
 
 use std::borrow::Borrow;
 use std::ops::Range;
 use std::sync::{Arc, Mutex};
-use std::{mem, slice};
+use std::{iter, mem, slice};
 
 // Command buffer implementation details:
 //
@@ -50,7 +50,7 @@ impl BufferSlice {
 }
 
 ///
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum Command {
     Dispatch(hal::WorkGroupCount),
     DispatchIndirect(gl::types::GLuint, buffer::Offset),
@@ -67,6 +67,21 @@ pub enum Command {
         base_vertex: hal::VertexOffset,
         instances: Range<hal::InstanceCount>,
     },
+    DrawIndirect {
+        primitive: gl::types::GLenum,
+        buffer: n::RawBuffer,
+        offset: buffer::Offset,
+        draw_count: hal::DrawCount,
+        stride: u32,
+    },
+    DrawIndexedIndirect {
+        primitive: gl::types::GLenum,
+        index_type: gl::types::GLenum,
+        buffer: n::RawBuffer,
+        offset: buffer::Offset,
+        draw_count: hal::DrawCount,
+        stride: u32,
+    },
     BindIndexBuffer(gl::types::GLuint),
     //BindVertexBuffers(BufferSlice),
     BindUniform {
@@ -76,6 +91,7 @@ pub enum Command {
     BindRasterizer {
         rasterizer: pso::Rasterizer,
     },
+    BindMultisampling(Option<pso::Multisampling>),
     BindDepth {
         depth: pso::DepthTest,
     },
@@ -85,7 +101,10 @@ pub enum Command {
         depth_range_ptr: BufferSlice,
     },
     SetScissors(u32, BufferSlice),
+    SetScissorTest(bool),
     SetBlendColor(pso::ColorValue),
+    SetDepthBias(pso::DepthBias),
+    SetLineWidth(f32),
 
     /// Clear floating-point color drawbuffer of bound framebuffer.
     ClearBufferColorF(DrawBuffer, [f32; 4]),
@@ -106,8 +125,15 @@ pub enum Command {
     BindTargetView(FrameBufferTarget, AttachmentPoint, n::ImageView),
     SetDrawColorBuffers(usize),
     SetPatchSize(gl::types::GLint),
+    SetPrimitiveRestart(pso::PrimitiveRestart),
     BindProgram(gl::types::GLuint),
     BindBlendSlot(ColorSlot, pso::ColorBlendDesc),
+    /// Bind stencil state, all dynamic values have been resolved already.
+    BindStencil(pso::StencilTest),
+    /// Enable writes to all color channels of all draw buffers.
+    UnlockColorMask,
+    /// Enable depth writes and writes to all stencil bits of both faces.
+    UnlockDepthStencilMask,
     BindAttribute(
         n::AttributeDesc,
         gl::types::GLuint,
@@ -116,12 +142,58 @@ pub enum Command {
     ),
     //UnbindAttribute(n::AttributeDesc),
     CopyBufferToBuffer(n::RawBuffer, n::RawBuffer, command::BufferCopy),
-    CopyBufferToTexture(n::RawBuffer, n::Texture, command::BufferImageCopy),
+    /// Update a region of a buffer with data stored in the command buffer.
+    UpdateBuffer(n::RawBuffer, buffer::Offset, BufferSlice),
+    CopyBufferToTexture {
+        buffer: n::RawBuffer,
+        texture: n::Texture,
+        target: n::TextureTarget,
+        region: command::BufferImageCopy,
+        format: gl::types::GLenum,
+        ty: gl::types::GLenum,
+    },
+    /// Upload compressed blocks, `size` is the number of bytes of the region.
+    CopyBufferToCompressedTexture {
+        buffer: n::RawBuffer,
+        texture: n::Texture,
+        target: n::TextureTarget,
+        region: command::BufferImageCopy,
+        format: gl::types::GLenum,
+        size: gl::types::GLsizei,
+    },
     CopyBufferToSurface(n::RawBuffer, n::Surface, command::BufferImageCopy),
-    CopyTextureToBuffer(n::Texture, n::RawBuffer, command::BufferImageCopy),
-    CopySurfaceToBuffer(n::Surface, n::RawBuffer, command::BufferImageCopy),
+    /// Read back a whole level of a texture.
+    CopyTextureToBuffer {
+        texture: n::Texture,
+        target: n::TextureTarget,
+        buffer: n::RawBuffer,
+        region: command::BufferImageCopy,
+        format: gl::types::GLenum,
+        ty: gl::types::GLenum,
+    },
+    /// Read back a region of an attachment of the bound read framebuffer.
+    CopyReadAttachmentToBuffer {
+        attachment: AttachmentPoint,
+        buffer: n::RawBuffer,
+        region: command::BufferImageCopy,
+        format: gl::types::GLenum,
+        ty: gl::types::GLenum,
+    },
     CopyImageToTexture(n::ImageKind, n::Texture, command::ImageCopy),
     CopyImageToSurface(n::ImageKind, n::Surface, command::ImageCopy),
+    /// Blit between the bound read and draw framebuffers.
+    BlitFrameBuffer {
+        src_bounds: Range<image::Offset>,
+        dst_bounds: Range<image::Offset>,
+        mask: gl::types::GLbitfield,
+        filter: gl::types::GLenum,
+    },
+
+    BeginQuery(n::Query, query::ControlFlags),
+    EndQuery(query::ControlFlags),
+    WriteTimestamp(n::Query),
+    /// Write the result of a query into a buffer at the given offset.
+    CopyQueryResult(n::Query, n::RawBuffer, buffer::Offset, query::ResultFlags),
 
     BindBufferRange(
         gl::types::GLenum,
@@ -130,15 +202,80 @@ pub enum Command {
         gl::types::GLintptr,
         gl::types::GLsizeiptr,
     ),
-    BindTexture(gl::types::GLenum, n::Texture),
+    BindTexture(gl::types::GLenum, n::Texture, n::TextureTarget),
+    /// Restrict sampling of the bound texture to a range of levels.
+    SetTextureLevels(n::TextureTarget, image::Level, image::Level),
+    /// Generate a range of mipmap levels of a texture with the given
+    /// total number of levels from the first one.
+    GenerateMipmap(
+        n::Texture,
+        n::TextureTarget,
+        Range<image::Level>,
+        image::Level,
+    ),
+    BindImageTexture {
+        unit: gl::types::GLuint,
+        texture: n::Texture,
+        level: image::Level,
+        layer: Option<image::Layer>,
+        format: gl::types::GLenum,
+    },
     BindSampler(gl::types::GLuint, n::Texture),
-    SetTextureSamplerSettings(gl::types::GLuint, n::Texture, image::SamplerInfo),
+    SetTextureSamplerSettings(
+        gl::types::GLuint,
+        n::Texture,
+        n::TextureTarget,
+        image::SamplerInfo,
+    ),
+
+    /// Debug group and marker labels, the buffer slice contains the name.
+    PushDebugGroup(BufferSlice),
+    PopDebugGroup,
+    InsertDebugMarker(BufferSlice),
 }
 
 pub type FrameBufferTarget = gl::types::GLenum;
 pub type AttachmentPoint = gl::types::GLenum;
 pub type DrawBuffer = gl::types::GLint;
 
+// Pixel transfer format, type and texel size in bytes for copying the
+// given aspects of an image.
+fn transfer_format(
+    format: format::Format,
+    aspects: format::Aspects,
+) -> Option<(gl::types::GLenum, gl::types::GLenum, u32)> {
+    use crate::hal::format::Format::*;
+
+    let desc = format.surface_desc();
+    if desc.is_compressed() {
+        return None;
+    }
+    if aspects.contains(format::Aspects::COLOR) {
+        return conv::image_format_to_gl(format)
+            .map(|(_, format, ty)| (format, ty, desc.bits as u32 / 8));
+    }
+    // The depth and stencil aspects of combined formats can also be
+    // transferred on their own, with smaller texels.
+    if aspects == format::Aspects::DEPTH | format::Aspects::STENCIL {
+        match format {
+            D24UnormS8Uint => Some((gl::DEPTH_STENCIL, gl::UNSIGNED_INT_24_8, 4)),
+            D32SfloatS8Uint => Some((gl::DEPTH_STENCIL, gl::FLOAT_32_UNSIGNED_INT_24_8_REV, 8)),
+            _ => None,
+        }
+    } else if aspects == format::Aspects::DEPTH {
+        match format {
+            D16Unorm => Some((gl::DEPTH_COMPONENT, gl::UNSIGNED_SHORT, 2)),
+            X8D24Unorm | D24UnormS8Uint => Some((gl::DEPTH_COMPONENT, gl::UNSIGNED_INT, 4)),
+            D32Sfloat | D32SfloatS8Uint => Some((gl::DEPTH_COMPONENT, gl::FLOAT, 4)),
+            _ => None,
+        }
+    } else if aspects == format::Aspects::STENCIL && desc.aspects.contains(aspects) {
+        Some((gl::STENCIL_INDEX, gl::UNSIGNED_BYTE, 1))
+    } else {
+        None
+    }
+}
+
 #[derive(Clone, Debug)]
 struct AttachmentClear {
     subpass_id: Option<pass::SubpassId>,
@@ -160,10 +297,22 @@ struct Cache {
     primitive: Option<gl::types::GLenum>,
     // Active index type, set by the current index buffer.
     index_type: Option<hal::IndexType>,
+    // Depth test state, set by the current pipeline.
+    depth: Option<pso::DepthTest>,
+    // Stencil test state, set by the current pipeline.
+    stencil: Option<pso::StencilTest>,
     // Stencil reference values (front, back).
     stencil_ref: Option<(pso::StencilValue, pso::StencilValue)>,
+    // Stencil read mask values (front, back).
+    stencil_read_mask: Option<(pso::StencilValue, pso::StencilValue)>,
+    // Stencil write mask values (front, back).
+    stencil_write_mask: Option<(pso::StencilValue, pso::StencilValue)>,
     // Blend color.
     blend_color: Option<pso::ColorValue>,
+    // Scissor rects, starting at index 0.
+    scissors: Vec<[i32; 4]>,
+    // Scissor test state.
+    scissor_test: bool,
     ///
     framebuffer: Option<(FrameBufferTarget, n::FrameBuffer)>,
     ///
@@ -171,8 +320,12 @@ struct Cache {
     error_state: bool,
     // Vertices per patch for tessellation primitives (patches).
     patch_size: Option<gl::types::GLint>,
+    // Primitive restart state, set by the current pipeline.
+    primitive_restart: pso::PrimitiveRestart,
     // Active program name.
     program: Option<gl::types::GLuint>,
+    // Control flags of the active occlusion query.
+    occlusion_query: Option<query::ControlFlags>,
     // Blend per attachment.
     blend_targets: Option<Vec<Option<pso::ColorBlendDesc>>>,
     // Maps bound vertex buffer offset (index) to handle.
@@ -190,12 +343,20 @@ impl Cache {
         Cache {
             primitive: None,
             index_type: None,
+            depth: None,
+            stencil: None,
             stencil_ref: None,
+            stencil_read_mask: None,
+            stencil_write_mask: None,
             blend_color: None,
+            scissors: Vec::new(),
+            scissor_test: false,
             framebuffer: None,
             error_state: false,
             patch_size: None,
+            primitive_restart: pso::PrimitiveRestart::Disabled,
             program: None,
+            occlusion_query: None,
             blend_targets: None,
             vertex_buffers: Vec::new(),
             vertex_buffer_descs: Vec::new(),
@@ -234,6 +395,8 @@ pub struct RawCommandBuffer {
     individual_reset: bool,
 
     fbo: Option<n::FrameBuffer>,
+    // Destination framebuffer for blits, `fbo` is used as the source.
+    blit_fbo: Option<n::FrameBuffer>,
     /// The framebuffer to use for rendering to the main targets (0 by default).
     ///
     /// Use this to set the framebuffer that will be used for the screen display targets created
@@ -256,6 +419,7 @@ pub struct RawCommandBuffer {
 impl RawCommandBuffer {
     pub(crate) fn new(
         fbo: Option<n::FrameBuffer>,
+        blit_fbo: Option<n::FrameBuffer>,
         limits: Limits,
         memory: Arc<Mutex<BufferMemory>>,
     ) -> Self {
@@ -285,6 +449,7 @@ impl RawCommandBuffer {
             id,
             individual_reset,
             fbo,
+            blit_fbo,
             display_fb: 0 as n::FrameBuffer,
             cache: Cache::new(),
             pass_cache: None,
@@ -378,6 +543,152 @@ impl RawCommandBuffer {
         }
     }
 
+    /// Bind the stencil state of the current pipeline, filling in the
+    /// dynamic values set on the command buffer.
+    fn update_stencil(&mut self) {
+        let (front, back) = match self.cache.stencil {
+            Some(pso::StencilTest::On { front, back }) => (front, back),
+            Some(pso::StencilTest::Off) => {
+                self.push_cmd(Command::BindStencil(pso::StencilTest::Off));
+                return;
+            }
+            None => return,
+        };
+
+        let refs = self.cache.stencil_ref.unwrap_or((0, 0));
+        let read_masks = self.cache.stencil_read_mask.unwrap_or((!0, !0));
+        let write_masks = self.cache.stencil_write_mask.unwrap_or((!0, !0));
+        let resolve = |state: pso::State<pso::StencilValue>, value| match state {
+            pso::State::Static(v) => pso::State::Static(v),
+            pso::State::Dynamic => pso::State::Static(value),
+        };
+
+        let stencil = pso::StencilTest::On {
+            front: pso::StencilFace {
+                reference: resolve(front.reference, refs.0),
+                mask_read: resolve(front.mask_read, read_masks.0),
+                mask_write: resolve(front.mask_write, write_masks.0),
+                ..front
+            },
+            back: pso::StencilFace {
+                reference: resolve(back.reference, refs.1),
+                mask_read: resolve(back.mask_read, read_masks.1),
+                mask_write: resolve(back.mask_write, write_masks.1),
+                ..back
+            },
+        };
+        self.push_cmd(Command::BindStencil(stencil));
+    }
+
+    /// Check that a buffer-image copy region lies within both resources.
+    fn validate_buffer_image_copy(
+        &mut self,
+        buffer: &n::Buffer,
+        image: &n::Image,
+        r: &command::BufferImageCopy,
+    ) -> bool {
+        let level = r.image_layers.level;
+        if level >= image.num_levels {
+            error!(
+                "Image level {} is out of bounds, the image has {} levels",
+                level, image.num_levels
+            );
+            self.cache.error_state = true;
+            return false;
+        }
+
+        let extent = image.extent.at_level(level);
+        if !texel_range_fits(r.image_offset.x, r.image_extent.width, extent.width)
+            || !texel_range_fits(r.image_offset.y, r.image_extent.height, extent.height)
+            || !texel_range_fits(r.image_offset.z, r.image_extent.depth, extent.depth)
+        {
+            error!(
+                "Image region {:?} with extent {:?} exceeds level {} of extent {:?}",
+                r.image_offset, r.image_extent, level, extent
+            );
+            self.cache.error_state = true;
+            return false;
+        }
+
+        // Compressed regions have to cover whole blocks, except for the
+        // partial blocks at the edges of the level.
+        let (block_width, block_height) = image.format.surface_desc().dim;
+        let aligned = |offset: i32, size: u32, max: u32, block: u8| {
+            let block = block as u32;
+            offset as u32 % block == 0 && (size % block == 0 || offset as u32 + size == max)
+        };
+        if !aligned(
+            r.image_offset.x,
+            r.image_extent.width,
+            extent.width,
+            block_width,
+        ) || !aligned(
+            r.image_offset.y,
+            r.image_extent.height,
+            extent.height,
+            block_height,
+        ) {
+            error!(
+                "Image region {:?} with extent {:?} is not aligned to the {}x{} blocks of {:?}",
+                r.image_offset, r.image_extent, block_width, block_height, image.format
+            );
+            self.cache.error_state = true;
+            return false;
+        }
+
+        let width = if r.buffer_width == 0 {
+            r.image_extent.width
+        } else {
+            r.buffer_width
+        };
+        let height = if r.buffer_height == 0 {
+            r.image_extent.height
+        } else {
+            r.buffer_height
+        };
+        let layers = (r.image_layers.layers.end - r.image_layers.layers.start) as u32;
+        let depth = r.image_extent.depth.saturating_mul(layers);
+        // Buffers hold only the copied aspects, at their own texel size.
+        // Sizes saturate on overflow, which always fails the range check.
+        let size = match transfer_format(image.format, r.image_layers.aspects) {
+            Some((_, _, texel_size)) => (width as u64 * height as u64)
+                .saturating_mul(depth as u64)
+                .saturating_mul(texel_size as u64),
+            None => conv::image_region_size(image.format, width, height, depth),
+        };
+        if !range_fits(r.buffer_offset, size, buffer.requirements.size) {
+            error!(
+                "Buffer region of {} bytes at offset {} exceeds the buffer size {}",
+                size, r.buffer_offset, buffer.requirements.size
+            );
+            self.cache.error_state = true;
+            return false;
+        }
+
+        true
+    }
+
+    /// Re-apply the color masks of the bound pipeline after they have been
+    /// unlocked for clearing, which must not be affected by them.
+    fn restore_color_masks(&mut self) {
+        if let Some(targets) = self.cache.blend_targets.clone() {
+            for (slot, target) in targets.into_iter().enumerate() {
+                if let Some(target) = target {
+                    self.push_cmd(Command::BindBlendSlot(slot as _, target));
+                }
+            }
+        }
+    }
+
+    /// Re-apply the depth and stencil write masks of the bound pipeline
+    /// after they have been unlocked for clearing.
+    fn restore_depth_stencil_masks(&mut self) {
+        if let Some(depth) = self.cache.depth {
+            self.push_cmd(Command::BindDepth { depth });
+        }
+        self.update_stencil();
+    }
+
     pub(crate) fn bind_attributes(&mut self) {
         let Cache {
             ref attributes,
@@ -509,8 +820,133 @@ impl RawCommandBuffer {
         let draw_buffers = self.add(&draw_buffers);
         self.push_cmd(Command::DrawBuffers(draw_buffers));
 
-        for cmd in clear_cmds {
-            self.push_cmd(cmd);
+        if !clear_cmds.is_empty() {
+            self.push_cmd(Command::UnlockColorMask);
+            self.push_cmd(Command::UnlockDepthStencilMask);
+            for cmd in clear_cmds {
+                self.push_cmd(cmd);
+            }
+            self.restore_color_masks();
+            self.restore_depth_stencil_masks();
+        }
+    }
+
+    /// Bind descriptor sets, flattening them into the global binding slots
+    /// remapped by the pipeline layout. Shared by graphics and compute.
+    fn bind_descriptor_sets<I>(&mut self, layout: &n::PipelineLayout, first_set: usize, sets: I)
+    where
+        I: IntoIterator,
+        I::Item: Borrow<n::DescriptorSet>,
+    {
+        let mut set = first_set as _;
+        let drd = &*layout.desc_remap_data.read().unwrap();
+
+        for desc_set in sets {
+            let desc_set = desc_set.borrow();
+            let bindings = desc_set.bindings.lock().unwrap();
+            for new_binding in &*bindings {
+                match new_binding {
+                    n::DescSetBindings::Buffer {
+                        ty: btype,
+                        binding,
+                        buffer,
+                        offset,
+                        size,
+                    } => {
+                        let target = match btype {
+                            n::BindingTypes::UniformBuffers => gl::UNIFORM_BUFFER,
+                            n::BindingTypes::StorageBuffers => gl::SHADER_STORAGE_BUFFER,
+                            n::BindingTypes::Images | n::BindingTypes::StorageImages => {
+                                panic!("Wrong desc set binding")
+                            }
+                        };
+                        for binding in drd.get_binding(*btype, set, *binding).unwrap() {
+                            self.push_cmd(Command::BindBufferRange(
+                                target, *binding, *buffer, *offset, *size,
+                            ))
+                        }
+                    }
+                    n::DescSetBindings::Texture(binding, texture, target, levels) => {
+                        for binding in drd
+                            .get_binding(n::BindingTypes::Images, set, *binding)
+                            .unwrap()
+                        {
+                            self.push_cmd(Command::BindTexture(*binding, *texture, *target));
+                            if let Some((start, end)) = *levels {
+                                self.push_cmd(Command::SetTextureLevels(*target, start, end));
+                            }
+                        }
+                    }
+                    n::DescSetBindings::StorageImage {
+                        binding,
+                        texture,
+                        level,
+                        layer,
+                        format,
+                    } => {
+                        for unit in drd
+                            .get_binding(n::BindingTypes::StorageImages, set, *binding)
+                            .unwrap()
+                        {
+                            self.push_cmd(Command::BindImageTexture {
+                                unit: *unit,
+                                texture: *texture,
+                                level: *level,
+                                layer: *layer,
+                                format: *format,
+                            })
+                        }
+                    }
+                    n::DescSetBindings::Sampler(binding, sampler) => {
+                        for binding in drd
+                            .get_binding(n::BindingTypes::Images, set, *binding)
+                            .unwrap()
+                        {
+                            self.push_cmd(Command::BindSampler(*binding, *sampler))
+                        }
+                    }
+                    n::DescSetBindings::SamplerInfo(binding, sinfo) => {
+                        let mut all_txts = drd
+                            .get_binding(n::BindingTypes::Images, set, *binding)
+                            .unwrap()
+                            .into_iter()
+                            .flat_map(|binding| {
+                                bindings.iter().filter_map(move |b| {
+                                    if let n::DescSetBindings::Texture(b, t, tt, _) = b {
+                                        let nbs =
+                                            drd.get_binding(n::BindingTypes::Images, set, *b)?;
+                                        if nbs.contains(binding) {
+                                            Some((*binding, *t, *tt))
+                                        } else {
+                                            None
+                                        }
+                                    } else {
+                                        None
+                                    }
+                                })
+                            })
+                            .collect::<Vec<_>>();
+
+                        // TODO: Check that other samplers aren't using the same
+                        // textures as in `all_txts` unless all the bindings of that
+                        // texture are gonna be unbound or the two samplers have
+                        // identical properties.
+
+                        all_txts.sort_unstable_by(|a, b| a.1.cmp(&b.1));
+                        all_txts.dedup_by(|a, b| a.1 == b.1);
+
+                        for (binding, txt, target) in all_txts {
+                            self.push_cmd(Command::SetTextureSamplerSettings(
+                                binding,
+                                txt,
+                                target,
+                                sinfo.clone(),
+                            ))
+                        }
+                    }
+                }
+            }
+            set += 1;
         }
     }
 }
@@ -580,8 +1016,9 @@ impl command::RawCommandBuffer<Backend> for RawCommandBuffer {
         unimplemented!()
     }
 
-    unsafe fn update_buffer(&mut self, _buffer: &n::Buffer, _offset: buffer::Offset, _data: &[u8]) {
-        unimplemented!()
+    unsafe fn update_buffer(&mut self, buffer: &n::Buffer, offset: buffer::Offset, data: &[u8]) {
+        let data_ptr = self.add_raw(data);
+        self.push_cmd(Command::UpdateBuffer(buffer.raw, offset, data_ptr));
     }
 
     unsafe fn begin_render_pass<T>(
@@ -676,12 +1113,8 @@ impl command::RawCommandBuffer<Backend> for RawCommandBuffer {
 
         match self.fbo {
             Some(fbo) => {
-                // TODO: reset color mask
                 // 2. ClearBuffer
-                let view = match image.kind {
-                    n::ImageKind::Surface(id) => n::ImageView::Surface(id),
-                    n::ImageKind::Texture(id) => n::ImageView::Texture(id, 0), //TODO
-                };
+                let view = image_layer_view(image.kind, 0, 0); //TODO
                 self.push_cmd(Command::BindFrameBuffer(gl::DRAW_FRAMEBUFFER, fbo));
                 self.push_cmd(Command::BindTargetView(
                     gl::DRAW_FRAMEBUFFER,
@@ -689,6 +1122,7 @@ impl command::RawCommandBuffer<Backend> for RawCommandBuffer {
                     view,
                 ));
                 self.push_cmd(Command::SetDrawColorBuffers(1));
+                self.push_cmd(Command::UnlockColorMask);
 
                 match image.channel {
                     ChannelType::Unorm
@@ -703,57 +1137,325 @@ impl command::RawCommandBuffer<Backend> for RawCommandBuffer {
                     ChannelType::Uint => self.push_cmd(Command::ClearBufferColorU(0, color.uint32)),
                     ChannelType::Sint => self.push_cmd(Command::ClearBufferColorI(0, color.int32)),
                 }
+
+                self.restore_color_masks();
             }
             None => {
                 // 1. glClear
-                let text = match image.kind {
-                    n::ImageKind::Texture(id) => id, //TODO
+                let (text, target) = match image.kind {
+                    n::ImageKind::Texture(id, target) => (id, target), //TODO
                     n::ImageKind::Surface(_id) => unimplemented!(),
                 };
 
-                self.push_cmd(Command::BindTexture(0, text));
+                self.push_cmd(Command::BindTexture(0, text, target));
                 self.push_cmd(Command::ClearTexture(color.float32));
             }
         }
     }
 
-    unsafe fn clear_attachments<T, U>(&mut self, _: T, _: U)
+    unsafe fn clear_attachments<T, U>(&mut self, clears: T, rects: U)
     where
         T: IntoIterator,
         T::Item: Borrow<command::AttachmentClear>,
         U: IntoIterator,
         U::Item: Borrow<pso::ClearRect>,
     {
-        unimplemented!()
+        // Clears are restricted to the rects by temporarily overriding
+        // the scissor state of the first viewport.
+        let clear_cmds = clears
+            .into_iter()
+            .filter_map(|clear| match *clear.borrow() {
+                command::AttachmentClear::Color { index, value } => {
+                    let draw_buffer = index as DrawBuffer;
+                    Some(match value {
+                        command::ClearColor::Float(cv) => {
+                            Command::ClearBufferColorF(draw_buffer, cv)
+                        }
+                        command::ClearColor::Uint(cv) => {
+                            Command::ClearBufferColorU(draw_buffer, cv)
+                        }
+                        command::ClearColor::Int(cv) => Command::ClearBufferColorI(draw_buffer, cv),
+                    })
+                }
+                command::AttachmentClear::DepthStencil { depth, stencil } => {
+                    if depth.is_some() || stencil.is_some() {
+                        Some(Command::ClearBufferDepthStencil(depth, stencil))
+                    } else {
+                        None
+                    }
+                }
+            })
+            .collect::<Vec<_>>();
+
+        if clear_cmds.is_empty() {
+            return;
+        }
+
+        // Setting a single scissor rect overrides all of them, which could
+        // not be restored for the other viewports.
+        if self.cache.scissors.len() > 1 {
+            error!("Clearing attachments with more than one scissor rect set is not supported");
+            self.cache.error_state = true;
+            return;
+        }
+
+        let mut cleared = false;
+        for rect in rects {
+            let rect = rect.borrow();
+            if rect.layers != (0..1) {
+                error!(
+                    "Clearing layers {:?} is not supported, only layer 0 can be cleared",
+                    rect.layers
+                );
+                self.cache.error_state = true;
+                continue;
+            }
+
+            if !cleared {
+                self.push_cmd(Command::SetScissorTest(true));
+                self.push_cmd(Command::UnlockColorMask);
+                self.push_cmd(Command::UnlockDepthStencilMask);
+                cleared = true;
+            }
+            let scissor = self.add::<i32>(&[
+                rect.rect.x as i32,
+                rect.rect.y as i32,
+                rect.rect.w as i32,
+                rect.rect.h as i32,
+            ]);
+            self.push_cmd(Command::SetScissors(0, scissor));
+
+            for cmd in &clear_cmds {
+                self.push_cmd(cmd.clone());
+            }
+        }
+
+        // Restore the scissor state set by the user, the clear rects
+        // must not affect subsequent draw calls.
+        if cleared {
+            if !self.cache.scissors.is_empty() {
+                let scissors = self.cache.scissors.clone();
+                let scissors_ptr = self.add::<[i32; 4]>(&scissors);
+                self.push_cmd(Command::SetScissors(0, scissors_ptr));
+            }
+            if !self.cache.scissor_test {
+                self.push_cmd(Command::SetScissorTest(false));
+            }
+            self.restore_color_masks();
+            self.restore_depth_stencil_masks();
+        }
     }
 
     unsafe fn resolve_image<T>(
         &mut self,
-        _src: &n::Image,
+        src: &n::Image,
         _src_layout: image::Layout,
-        _dst: &n::Image,
+        dst: &n::Image,
         _dst_layout: image::Layout,
-        _regions: T,
+        regions: T,
     ) where
         T: IntoIterator,
         T::Item: Borrow<command::ImageResolve>,
     {
-        unimplemented!()
+        let (src_fbo, dst_fbo) = match (self.fbo, self.blit_fbo) {
+            (Some(src_fbo), Some(dst_fbo)) => (src_fbo, dst_fbo),
+            _ => {
+                error!("Resolving images requires framebuffer support");
+                self.cache.error_state = true;
+                return;
+            }
+        };
+
+        // Blitting from a multisampled framebuffer resolves the samples.
+        for region in regions {
+            let r = region.borrow();
+            let src_view = image_layer_view(
+                src.kind,
+                r.src_subresource.level,
+                r.src_subresource.layers.start,
+            );
+            let dst_view = image_layer_view(
+                dst.kind,
+                r.dst_subresource.level,
+                r.dst_subresource.layers.start,
+            );
+            let bounds = |offset: image::Offset| {
+                offset..image::Offset {
+                    x: offset.x + r.extent.width as i32,
+                    y: offset.y + r.extent.height as i32,
+                    z: offset.z + r.extent.depth as i32,
+                }
+            };
+
+            self.push_cmd(Command::BindFrameBuffer(gl::READ_FRAMEBUFFER, src_fbo));
+            self.push_cmd(Command::BindTargetView(
+                gl::READ_FRAMEBUFFER,
+                gl::COLOR_ATTACHMENT0,
+                src_view,
+            ));
+            self.push_cmd(Command::BindFrameBuffer(gl::DRAW_FRAMEBUFFER, dst_fbo));
+            self.push_cmd(Command::BindTargetView(
+                gl::DRAW_FRAMEBUFFER,
+                gl::COLOR_ATTACHMENT0,
+                dst_view,
+            ));
+            self.push_cmd(Command::BlitFrameBuffer {
+                src_bounds: bounds(r.src_offset),
+                dst_bounds: bounds(r.dst_offset),
+                mask: gl::COLOR_BUFFER_BIT,
+                filter: gl::NEAREST,
+            });
+        }
     }
 
     unsafe fn blit_image<T>(
         &mut self,
-        _src: &n::Image,
+        src: &n::Image,
         _src_layout: image::Layout,
-        _dst: &n::Image,
+        dst: &n::Image,
         _dst_layout: image::Layout,
-        _filter: image::Filter,
-        _regions: T,
+        filter: image::Filter,
+        regions: T,
     ) where
         T: IntoIterator,
         T::Item: Borrow<command::ImageBlit>,
     {
-        unimplemented!()
+        let (src_fbo, dst_fbo) = match (self.fbo, self.blit_fbo) {
+            (Some(src_fbo), Some(dst_fbo)) => (src_fbo, dst_fbo),
+            _ => {
+                error!("Blitting images requires framebuffer support");
+                self.cache.error_state = true;
+                return;
+            }
+        };
+
+        let src_aspects = src.format.surface_desc().aspects;
+        if src_aspects != dst.format.surface_desc().aspects {
+            error!(
+                "Can't blit between images with different aspects ({:?} and {:?})",
+                src.format, dst.format
+            );
+            self.cache.error_state = true;
+            return;
+        }
+
+        let (attachment, mask) = if src_aspects.contains(format::Aspects::COLOR) {
+            (gl::COLOR_ATTACHMENT0, gl::COLOR_BUFFER_BIT)
+        } else {
+            let mut mask = 0;
+            if src_aspects.contains(format::Aspects::DEPTH) {
+                mask |= gl::DEPTH_BUFFER_BIT;
+            }
+            if src_aspects.contains(format::Aspects::STENCIL) {
+                mask |= gl::STENCIL_BUFFER_BIT;
+            }
+            let attachment = if mask == gl::DEPTH_BUFFER_BIT {
+                gl::DEPTH_ATTACHMENT
+            } else if mask == gl::STENCIL_BUFFER_BIT {
+                gl::STENCIL_ATTACHMENT
+            } else {
+                gl::DEPTH_STENCIL_ATTACHMENT
+            };
+            (attachment, mask)
+        };
+
+        let filter = match filter {
+            image::Filter::Nearest => gl::NEAREST,
+            image::Filter::Linear if mask == gl::COLOR_BUFFER_BIT => gl::LINEAR,
+            image::Filter::Linear => {
+                error!("Depth-stencil images can only be blitted with nearest filtering");
+                self.cache.error_state = true;
+                return;
+            }
+        };
+
+        for region in regions {
+            let r = region.borrow();
+            let src_layers = r.src_subresource.layers.clone();
+            let dst_layers = r.dst_subresource.layers.clone();
+            if src_layers.end - src_layers.start != dst_layers.end - dst_layers.start {
+                error!(
+                    "Can't blit layers {:?} into a different number of layers {:?}",
+                    src_layers, dst_layers
+                );
+                self.cache.error_state = true;
+                continue;
+            }
+
+            if r.src_bounds.end.z - r.src_bounds.start.z > 1
+                || r.dst_bounds.end.z - r.dst_bounds.start.z > 1
+            {
+                warn!("Blitting 3D images is limited to the first depth slice");
+            }
+
+            // Framebuffer blits only handle a single layer at a time, which
+            // also covers the faces of cube maps.
+            for (src_layer, dst_layer) in src_layers.zip(dst_layers) {
+                let src_view = image_layer_view(src.kind, r.src_subresource.level, src_layer);
+                let dst_view = image_layer_view(dst.kind, r.dst_subresource.level, dst_layer);
+
+                self.push_cmd(Command::BindFrameBuffer(gl::READ_FRAMEBUFFER, src_fbo));
+                self.push_cmd(Command::BindTargetView(
+                    gl::READ_FRAMEBUFFER,
+                    attachment,
+                    src_view,
+                ));
+                self.push_cmd(Command::BindFrameBuffer(gl::DRAW_FRAMEBUFFER, dst_fbo));
+                self.push_cmd(Command::BindTargetView(
+                    gl::DRAW_FRAMEBUFFER,
+                    attachment,
+                    dst_view,
+                ));
+                self.push_cmd(Command::BlitFrameBuffer {
+                    src_bounds: r.src_bounds.clone(),
+                    dst_bounds: r.dst_bounds.clone(),
+                    mask,
+                    filter,
+                });
+            }
+        }
+    }
+
+    unsafe fn generate_mipmaps(
+        &mut self,
+        image: &n::Image,
+        _extent: image::Extent,
+        levels: Range<image::Level>,
+        layers: Range<image::Layer>,
+    ) {
+        if levels.end > image.num_levels || layers.end > image.num_layers {
+            error!(
+                "Levels {:?} and layers {:?} are out of bounds, the image has {} levels and {} layers",
+                levels, layers, image.num_levels, image.num_layers
+            );
+            self.cache.error_state = true;
+            return;
+        }
+        if levels.len() <= 1 {
+            return;
+        }
+
+        match image.kind {
+            // `glGenerateMipmap` is part of framebuffer object support, and
+            // always fills all layers of the texture.
+            n::ImageKind::Texture(texture, target)
+                if self.fbo.is_some() && layers == (0..image.num_layers) =>
+            {
+                self.push_cmd(Command::GenerateMipmap(
+                    texture,
+                    target,
+                    levels,
+                    image.num_levels,
+                ));
+            }
+            _ => {
+                error!(
+                    "Generating mipmaps of layers {:?} of {:?} is not supported",
+                    layers, image.kind
+                );
+                self.cache.error_state = true;
+            }
+        }
     }
 
     unsafe fn bind_index_buffer(&mut self, ibv: buffer::IndexBufferView<Backend>) {
@@ -839,13 +1541,24 @@ impl command::RawCommandBuffer<Backend> for RawCommandBuffer {
         let mut len = 0;
         for scissor in scissors {
             let scissor = scissor.borrow();
-            let scissor = &[
+            if scissor.w < 0 || scissor.h < 0 {
+                error!("Scissor rect {:?} must not have a negative size", scissor);
+                self.cache.error_state = true;
+            }
+            // Negative sizes are clamped as they would raise GL errors.
+            let scissor = [
                 scissor.x as i32,
                 scissor.y as i32,
-                scissor.w as i32,
-                scissor.h as i32,
+                scissor.w.max(0) as i32,
+                scissor.h.max(0) as i32,
             ];
-            scissors_ptr.append(self.add::<i32>(scissor));
+            scissors_ptr.append(self.add::<i32>(&scissor));
+
+            let index = first_scissor as usize + len;
+            if self.cache.scissors.len() <= index {
+                self.cache.scissors.resize(index + 1, [0; 4]);
+            }
+            self.cache.scissors[index] = scissor;
             len += 1;
         }
 
@@ -855,6 +1568,10 @@ impl command::RawCommandBuffer<Backend> for RawCommandBuffer {
                 self.cache.error_state = true;
             }
             n if n + first_scissor as usize <= self.limits.max_viewports => {
+                if !self.cache.scissor_test {
+                    self.cache.scissor_test = true;
+                    self.push_cmd(Command::SetScissorTest(true));
+                }
                 self.push_cmd(Command::SetScissors(first_scissor, scissors_ptr));
             }
             _ => {
@@ -866,35 +1583,32 @@ impl command::RawCommandBuffer<Backend> for RawCommandBuffer {
 
     unsafe fn set_stencil_reference(&mut self, faces: pso::Face, value: pso::StencilValue) {
         assert!(!faces.is_empty());
-
-        let mut front = 0;
-        let mut back = 0;
-
-        if let Some((last_front, last_back)) = self.cache.stencil_ref {
-            front = last_front;
-            back = last_back;
-        }
-
-        if faces.contains(pso::Face::FRONT) {
-            front = value;
-        }
-
-        if faces.contains(pso::Face::BACK) {
-            back = value;
-        }
-
-        // Only cache the stencil references values until
-        // we assembled all the pieces to set the stencil state
-        // from the pipeline.
-        self.cache.stencil_ref = Some((front, back));
+        self.cache.stencil_ref = Some(update_face_values(
+            self.cache.stencil_ref.unwrap_or((0, 0)),
+            faces,
+            value,
+        ));
+        self.update_stencil();
     }
 
-    unsafe fn set_stencil_read_mask(&mut self, _faces: pso::Face, _value: pso::StencilValue) {
-        unimplemented!();
+    unsafe fn set_stencil_read_mask(&mut self, faces: pso::Face, value: pso::StencilValue) {
+        assert!(!faces.is_empty());
+        self.cache.stencil_read_mask = Some(update_face_values(
+            self.cache.stencil_read_mask.unwrap_or((!0, !0)),
+            faces,
+            value,
+        ));
+        self.update_stencil();
     }
 
-    unsafe fn set_stencil_write_mask(&mut self, _faces: pso::Face, _value: pso::StencilValue) {
-        unimplemented!();
+    unsafe fn set_stencil_write_mask(&mut self, faces: pso::Face, value: pso::StencilValue) {
+        assert!(!faces.is_empty());
+        self.cache.stencil_write_mask = Some(update_face_values(
+            self.cache.stencil_write_mask.unwrap_or((!0, !0)),
+            faces,
+            value,
+        ));
+        self.update_stencil();
     }
 
     unsafe fn set_blend_constants(&mut self, cv: pso::ColorValue) {
@@ -908,18 +1622,22 @@ impl command::RawCommandBuffer<Backend> for RawCommandBuffer {
         warn!("Depth bounds test is not supported");
     }
 
-    unsafe fn set_line_width(&mut self, _width: f32) {
-        unimplemented!()
+    unsafe fn set_line_width(&mut self, width: f32) {
+        self.push_cmd(Command::SetLineWidth(width));
     }
 
-    unsafe fn set_depth_bias(&mut self, _depth_bias: pso::DepthBias) {
-        unimplemented!()
+    unsafe fn set_depth_bias(&mut self, depth_bias: pso::DepthBias) {
+        if depth_bias.clamp != 0.0 {
+            warn!("Depth bias clamping is not supported");
+        }
+        self.push_cmd(Command::SetDepthBias(depth_bias));
     }
 
     unsafe fn bind_graphics_pipeline(&mut self, pipeline: &n::GraphicsPipeline) {
         let n::GraphicsPipeline {
             primitive,
             patch_size,
+            primitive_restart,
             program,
             ref blend_targets,
             ref attributes,
@@ -927,6 +1645,9 @@ impl command::RawCommandBuffer<Backend> for RawCommandBuffer {
             ref uniforms,
             rasterizer,
             depth,
+            stencil,
+            ref multisampling,
+            ref baked_states,
         } = *pipeline;
 
         if self.cache.primitive != Some(primitive) {
@@ -940,6 +1661,11 @@ impl command::RawCommandBuffer<Backend> for RawCommandBuffer {
             }
         }
 
+        if self.cache.primitive_restart != primitive_restart {
+            self.cache.primitive_restart = primitive_restart;
+            self.push_cmd(Command::SetPrimitiveRestart(primitive_restart));
+        }
+
         if self.cache.program != Some(program) {
             self.cache.program = Some(program);
             self.push_cmd(Command::BindProgram(program));
@@ -953,12 +1679,32 @@ impl command::RawCommandBuffer<Backend> for RawCommandBuffer {
 
         self.update_blend_targets(blend_targets);
 
+        if let Some(ref viewport) = baked_states.viewport {
+            self.set_viewports(0, iter::once(viewport));
+        }
+
+        if let Some(ref rect) = baked_states.scissor {
+            self.set_scissors(0, iter::once(rect));
+        }
+
+        if let Some(cv) = baked_states.blend_color {
+            self.set_blend_constants(cv);
+        }
+
         self.push_cmd(Command::BindRasterizer { 
             rasterizer, 
         });
+        self.cache.depth = Some(depth);
         self.push_cmd(Command::BindDepth { 
             depth,
         });
+
+        self.push_cmd(Command::BindMultisampling(multisampling.clone()));
+
+        if self.cache.stencil != Some(stencil) {
+            self.cache.stencil = Some(stencil);
+            self.update_stencil();
+        }
     }
 
     unsafe fn bind_graphics_descriptor_sets<I, J>(
@@ -974,93 +1720,7 @@ impl command::RawCommandBuffer<Backend> for RawCommandBuffer {
         J::Item: Borrow<command::DescriptorSetOffset>,
     {
         assert!(offsets.into_iter().next().is_none()); // TODO: offsets unsupported
-
-        let mut set = first_set as _;
-        let drd = &*layout.desc_remap_data.read().unwrap();
-
-        for desc_set in sets {
-            let desc_set = desc_set.borrow();
-            let bindings = desc_set.bindings.lock().unwrap();
-            for new_binding in &*bindings {
-                match new_binding {
-                    n::DescSetBindings::Buffer {
-                        ty: btype,
-                        binding,
-                        buffer,
-                        offset,
-                        size,
-                    } => {
-                        let btype = match btype {
-                            n::BindingTypes::UniformBuffers => gl::UNIFORM_BUFFER,
-                            n::BindingTypes::Images => panic!("Wrong desc set binding"),
-                        };
-                        for binding in drd
-                            .get_binding(n::BindingTypes::UniformBuffers, set, *binding)
-                            .unwrap()
-                        {
-                            self.push_cmd(Command::BindBufferRange(
-                                btype, *binding, *buffer, *offset, *size,
-                            ))
-                        }
-                    }
-                    n::DescSetBindings::Texture(binding, texture) => {
-                        for binding in drd
-                            .get_binding(n::BindingTypes::Images, set, *binding)
-                            .unwrap()
-                        {
-                            self.push_cmd(Command::BindTexture(*binding, *texture))
-                        }
-                    }
-                    n::DescSetBindings::Sampler(binding, sampler) => {
-                        for binding in drd
-                            .get_binding(n::BindingTypes::Images, set, *binding)
-                            .unwrap()
-                        {
-                            self.push_cmd(Command::BindSampler(*binding, *sampler))
-                        }
-                    }
-                    n::DescSetBindings::SamplerInfo(binding, sinfo) => {
-                        let mut all_txts = drd
-                            .get_binding(n::BindingTypes::Images, set, *binding)
-                            .unwrap()
-                            .into_iter()
-                            .flat_map(|binding| {
-                                bindings.iter().filter_map(move |b| {
-                                    if let n::DescSetBindings::Texture(b, t) = b {
-                                        let nbs =
-                                            drd.get_binding(n::BindingTypes::Images, set, *b)?;
-                                        if nbs.contains(binding) {
-                                            Some((*binding, *t))
-                                        } else {
-                                            None
-                                        }
-                                    } else {
-                                        None
-                                    }
-                                })
-                            })
-                            .collect::<Vec<_>>();
-
-                        // TODO: Check that other samplers aren't using the same
-                        // textures as in `all_txts` unless all the bindings of that
-                        // texture are gonna be unbound or the two samplers have
-                        // identical properties.
-
-                        all_txts.sort_unstable_by(|a, b| a.1.cmp(&b.1));
-                        all_txts.dedup_by(|a, b| a.1 == b.1);
-
-                        for (binding, txt) in all_txts {
-                            self.push_cmd(Command::SetTextureSamplerSettings(
-                                binding,
-                                txt,
-                                sinfo.clone(),
-                            ))
-                        }
-                    }
-                }
-            }
-            set += 1;
-        }
+        self.bind_descriptor_sets(layout, first_set, sets);
     }
 
     unsafe fn bind_compute_pipeline(&mut self, pipeline: &n::ComputePipeline) {
@@ -1074,17 +1734,18 @@ impl command::RawCommandBuffer<Backend> for RawCommandBuffer {
 
     unsafe fn bind_compute_descriptor_sets<I, J>(
         &mut self,
-        _layout: &n::PipelineLayout,
-        _first_set: usize,
-        _sets: I,
-        _offsets: J,
+        layout: &n::PipelineLayout,
+        first_set: usize,
+        sets: I,
+        offsets: J,
     ) where
         I: IntoIterator,
         I::Item: Borrow<n::DescriptorSet>,
         J: IntoIterator,
         J::Item: Borrow<command::DescriptorSetOffset>,
     {
-        // TODO
+        assert!(offsets.into_iter().next().is_none()); // TODO: offsets unsupported
+        self.bind_descriptor_sets(layout, first_set, sets);
     }
 
     unsafe fn dispatch(&mut self, count: hal::WorkGroupCount) {
@@ -1104,6 +1765,18 @@ impl command::RawCommandBuffer<Backend> for RawCommandBuffer {
 
         for region in regions {
             let r = region.borrow().clone();
+            if !range_fits(r.src, r.size, src.requirements.size)
+                || !range_fits(r.dst, r.size, dst.requirements.size)
+            {
+                error!("Buffer copy region {:?} is out of bounds", r);
+                self.cache.error_state = true;
+                continue;
+            }
+            if src.raw == dst.raw && ranges_overlap(r.src, r.dst, r.size) {
+                error!("Buffer copy region {:?} overlaps itself", r);
+                self.cache.error_state = true;
+                continue;
+            }
             let cmd = Command::CopyBufferToBuffer(src.raw, dst.raw, r);
             self.push_cmd(cmd);
         }
@@ -1130,7 +1803,7 @@ impl command::RawCommandBuffer<Backend> for RawCommandBuffer {
             let r = region.borrow().clone();
             let cmd = match dst.kind {
                 n::ImageKind::Surface(s) => Command::CopyImageToSurface(src.kind, s, r),
-                n::ImageKind::Texture(t) => Command::CopyImageToTexture(src.kind, t, r),
+                n::ImageKind::Texture(t, _) => Command::CopyImageToTexture(src.kind, t, r),
             };
             self.push_cmd(cmd);
         }
@@ -1154,11 +1827,100 @@ impl command::RawCommandBuffer<Backend> for RawCommandBuffer {
 
         for region in regions {
             let r = region.borrow().clone();
-            let cmd = match dst.kind {
-                n::ImageKind::Surface(s) => Command::CopyBufferToSurface(src.raw, s, r),
-                n::ImageKind::Texture(t) => Command::CopyBufferToTexture(src.raw, t, r),
+            if !self.validate_buffer_image_copy(src, dst, &r) {
+                continue;
+            }
+            let (texture, target) = match dst.kind {
+                n::ImageKind::Surface(s) => {
+                    self.push_cmd(Command::CopyBufferToSurface(src.raw, s, r));
+                    continue;
+                }
+                n::ImageKind::Texture(t, target) => (t, target),
             };
-            self.push_cmd(cmd);
+
+            if target == gl::TEXTURE_2D && r.image_layers.layers != (0..1) {
+                warn!(
+                    "Copying image layers {:?} is not supported",
+                    r.image_layers.layers
+                );
+            }
+
+            // The faces of a cube map are separate images in GL,
+            // so they are uploaded one layer at a time.
+            let regions = if target == gl::TEXTURE_CUBE_MAP {
+                let width = if r.buffer_width == 0 {
+                    r.image_extent.width
+                } else {
+                    r.buffer_width
+                };
+                let height = if r.buffer_height == 0 {
+                    r.image_extent.height
+                } else {
+                    r.buffer_height
+                };
+                let layer_size = conv::image_region_size(dst.format, width, height, 1);
+                r.image_layers
+                    .layers
+                    .clone()
+                    .enumerate()
+                    .map(|(i, layer)| command::BufferImageCopy {
+                        buffer_offset: r.buffer_offset + i as u64 * layer_size,
+                        image_layers: image::SubresourceLayers {
+                            layers: layer..layer + 1,
+                            ..r.image_layers.clone()
+                        },
+                        ..r.clone()
+                    })
+                    .collect()
+            } else {
+                vec![r]
+            };
+
+            for r in regions {
+                if dst.format.surface_desc().is_compressed() {
+                    if r.buffer_width != 0 && r.buffer_width != r.image_extent.width {
+                        warn!("Compressed image copies must be tightly packed in the buffer");
+                    }
+                    let (internal_format, _, _) = conv::image_format_to_gl(dst.format).unwrap();
+                    let size = conv::image_region_size(
+                        dst.format,
+                        r.image_extent.width,
+                        r.image_extent.height,
+                        (r.image_layers.layers.end - r.image_layers.layers.start) as _,
+                    );
+                    self.push_cmd(Command::CopyBufferToCompressedTexture {
+                        buffer: src.raw,
+                        texture,
+                        target,
+                        region: r,
+                        format: internal_format,
+                        size: size as _,
+                    });
+                    continue;
+                }
+
+                let aspects = r.image_layers.aspects;
+                let (format, ty, _) = match transfer_format(dst.format, aspects) {
+                    Some(f) => f,
+                    None => {
+                        error!(
+                            "Unsupported copy of {:?} into {:?} image",
+                            aspects, dst.format
+                        );
+                        self.cache.error_state = true;
+                        continue;
+                    }
+                };
+
+                self.push_cmd(Command::CopyBufferToTexture {
+                    buffer: src.raw,
+                    texture,
+                    target,
+                    region: r,
+                    format,
+                    ty,
+                });
+            }
         }
 
         if self.buf.size == old_size {
@@ -1180,11 +1942,98 @@ impl command::RawCommandBuffer<Backend> for RawCommandBuffer {
 
         for region in regions {
             let r = region.borrow().clone();
-            let cmd = match src.kind {
-                n::ImageKind::Surface(s) => Command::CopySurfaceToBuffer(s, dst.raw, r),
-                n::ImageKind::Texture(t) => Command::CopyTextureToBuffer(t, dst.raw, r),
+            if !self.validate_buffer_image_copy(dst, src, &r) {
+                continue;
+            }
+            let aspects = r.image_layers.aspects;
+            let (format, ty, texel_size) = match transfer_format(src.format, aspects) {
+                Some(f) => f,
+                None => {
+                    error!(
+                        "Unsupported copy of {:?} from {:?} image",
+                        aspects, src.format
+                    );
+                    self.cache.error_state = true;
+                    continue;
+                }
             };
-            self.push_cmd(cmd);
+
+            let fbo = match (self.fbo, src.kind) {
+                (Some(fbo), _) => fbo,
+                (None, n::ImageKind::Texture(texture, target)) => {
+                    // Without framebuffers whole levels are read back, including
+                    // every layer of array textures.
+                    let array = target == gl::TEXTURE_1D_ARRAY
+                        || target == gl::TEXTURE_2D_ARRAY
+                        || target == gl::TEXTURE_CUBE_MAP_ARRAY;
+                    let whole_level = r.image_offset == (image::Offset { x: 0, y: 0, z: 0 })
+                        && r.image_extent == src.extent.at_level(r.image_layers.level)
+                        && r.image_layers.layers.end - r.image_layers.layers.start == 1
+                        && (!array || src.num_layers == 1);
+                    if !whole_level {
+                        error!("Partial texture copies require framebuffer support");
+                        self.cache.error_state = true;
+                        continue;
+                    }
+                    self.push_cmd(Command::CopyTextureToBuffer {
+                        texture,
+                        target,
+                        buffer: dst.raw,
+                        region: r,
+                        format,
+                        ty,
+                    });
+                    continue;
+                }
+                (None, n::ImageKind::Surface(_)) => {
+                    error!("Copying from a surface requires framebuffer support");
+                    self.cache.error_state = true;
+                    continue;
+                }
+            };
+
+            // Pixels are read back from the auxiliary framebuffer of the pool.
+            let attachment = if aspects.contains(format::Aspects::COLOR) {
+                gl::COLOR_ATTACHMENT0
+            } else if aspects.contains(format::Aspects::DEPTH) {
+                gl::DEPTH_ATTACHMENT
+            } else {
+                gl::STENCIL_ATTACHMENT
+            };
+
+            // Each layer is read back separately, one after another in the buffer.
+            let width = if r.buffer_width == 0 {
+                r.image_extent.width
+            } else {
+                r.buffer_width
+            };
+            let height = if r.buffer_height == 0 {
+                r.image_extent.height
+            } else {
+                r.buffer_height
+            };
+            let layer_size =
+                width as u64 * height as u64 * r.image_extent.depth as u64 * texel_size as u64;
+            for (i, layer) in r.image_layers.layers.clone().enumerate() {
+                let mut layer_region = r.clone();
+                layer_region.buffer_offset += i as u64 * layer_size;
+                layer_region.image_layers.layers = layer..layer + 1;
+
+                let view = image_layer_view(src.kind, r.image_layers.level, layer);
+                self.push_cmd(Command::BindFrameBuffer(gl::READ_FRAMEBUFFER, fbo));
+                self.push_cmd(Command::BindTargetView(
+                    gl::READ_FRAMEBUFFER,
+                    attachment,
+                    view,
+                ));
+                self.push_cmd(Command::CopyReadAttachmentToBuffer {
+                    attachment,
+                    buffer: dst.raw,
+                    region: layer_region,
+                    format,
+                    ty,
+                });
+            }
         }
 
         if self.buf.size == old_size {
@@ -1251,50 +2100,175 @@ impl command::RawCommandBuffer<Backend> for RawCommandBuffer {
 
     unsafe fn draw_indirect(
         &mut self,
-        _buffer: &n::Buffer,
-        _offset: buffer::Offset,
-        _draw_count: hal::DrawCount,
-        _stride: u32,
+        buffer: &n::Buffer,
+        offset: buffer::Offset,
+        draw_count: hal::DrawCount,
+        stride: u32,
     ) {
-        unimplemented!()
+        self.bind_attributes();
+
+        match self.cache.primitive {
+            Some(primitive) => {
+                self.push_cmd(Command::DrawIndirect {
+                    primitive,
+                    buffer: buffer.raw,
+                    offset,
+                    draw_count,
+                    stride,
+                });
+            }
+            None => {
+                warn!("No primitive bound. An active pipeline needs to be bound before calling `draw_indirect`.");
+                self.cache.error_state = true;
+            }
+        }
     }
 
     unsafe fn draw_indexed_indirect(
         &mut self,
-        _buffer: &n::Buffer,
-        _offset: buffer::Offset,
-        _draw_count: hal::DrawCount,
-        _stride: u32,
+        buffer: &n::Buffer,
+        offset: buffer::Offset,
+        draw_count: hal::DrawCount,
+        stride: u32,
     ) {
-        unimplemented!()
+        self.bind_attributes();
+
+        let index_type = match self.cache.index_type {
+            Some(hal::IndexType::U16) => gl::UNSIGNED_SHORT,
+            Some(hal::IndexType::U32) => gl::UNSIGNED_INT,
+            None => {
+                warn!("No index type bound. An index buffer needs to be bound before calling `draw_indexed_indirect`.");
+                self.cache.error_state = true;
+                return;
+            }
+        };
+        match self.cache.primitive {
+            Some(primitive) => {
+                self.push_cmd(Command::DrawIndexedIndirect {
+                    primitive,
+                    index_type,
+                    buffer: buffer.raw,
+                    offset,
+                    draw_count,
+                    stride,
+                });
+            }
+            None => {
+                warn!("No primitive bound. An active pipeline needs to be bound before calling `draw_indexed_indirect`.");
+                self.cache.error_state = true;
+            }
+        }
     }
 
-    unsafe fn begin_query(&mut self, _query: query::Query<Backend>, _flags: query::ControlFlags) {
-        unimplemented!()
+    unsafe fn begin_query(&mut self, query: query::Query<Backend>, flags: query::ControlFlags) {
+        match query.pool.ty {
+            query::Type::Occlusion => {
+                if self.cache.occlusion_query.is_some() {
+                    error!("Only one occlusion query can be active at a time");
+                    self.cache.error_state = true;
+                    return;
+                }
+                self.cache.occlusion_query = Some(flags);
+                let name = query.pool.queries[query.id as usize];
+                self.push_cmd(Command::BeginQuery(name, flags));
+            }
+            ty => {
+                error!("Query type {:?} can't be used with begin_query", ty);
+                self.cache.error_state = true;
+            }
+        }
     }
 
     unsafe fn copy_query_pool_results(
         &mut self,
-        _pool: &(),
-        _queries: Range<query::Id>,
-        _buffer: &n::Buffer,
-        _offset: buffer::Offset,
-        _stride: buffer::Offset,
-        _flags: query::ResultFlags,
+        pool: &n::QueryPool,
+        queries: Range<query::Id>,
+        buffer: &n::Buffer,
+        offset: buffer::Offset,
+        stride: buffer::Offset,
+        flags: query::ResultFlags,
     ) {
-        unimplemented!()
+        if queries.end as usize > pool.queries.len() {
+            error!(
+                "Queries {:?} are out of bounds, the pool has {} queries",
+                queries,
+                pool.queries.len()
+            );
+            self.cache.error_state = true;
+            return;
+        }
+
+        let value_size = if flags.contains(query::ResultFlags::BITS_64) {
+            8
+        } else {
+            4
+        };
+        let entry_size = if flags.contains(query::ResultFlags::WITH_AVAILABILITY) {
+            2 * value_size
+        } else {
+            value_size
+        };
+
+        for (i, id) in queries.enumerate() {
+            let entry_offset = (i as u64)
+                .checked_mul(stride)
+                .and_then(|o| o.checked_add(offset))
+                .filter(|&o| range_fits(o, entry_size, buffer.requirements.size));
+            match entry_offset {
+                Some(entry_offset) => {
+                    let query = pool.queries[id as usize];
+                    self.push_cmd(Command::CopyQueryResult(
+                        query,
+                        buffer.raw,
+                        entry_offset,
+                        flags,
+                    ));
+                }
+                None => {
+                    error!(
+                        "Result of query {} exceeds the buffer size {}",
+                        id, buffer.requirements.size
+                    );
+                    self.cache.error_state = true;
+                    return;
+                }
+            }
+        }
     }
 
-    unsafe fn end_query(&mut self, _query: query::Query<Backend>) {
-        unimplemented!()
+    unsafe fn end_query(&mut self, query: query::Query<Backend>) {
+        match query.pool.ty {
+            query::Type::Occlusion => match self.cache.occlusion_query.take() {
+                Some(flags) => self.push_cmd(Command::EndQuery(flags)),
+                None => {
+                    error!("No active occlusion query to end");
+                    self.cache.error_state = true;
+                }
+            },
+            ty => {
+                error!("Query type {:?} can't be used with end_query", ty);
+                self.cache.error_state = true;
+            }
+        }
     }
 
-    unsafe fn reset_query_pool(&mut self, _pool: &(), _queries: Range<query::Id>) {
-        unimplemented!()
+    unsafe fn reset_query_pool(&mut self, _pool: &n::QueryPool, _queries: Range<query::Id>) {
+        // Nothing to do, GL resets queries implicitly when they begin.
     }
 
-    unsafe fn write_timestamp(&mut self, _: pso::PipelineStage, _: query::Query<Backend>) {
-        unimplemented!()
+    unsafe fn write_timestamp(&mut self, _: pso::PipelineStage, query: query::Query<Backend>) {
+        // GL records the timestamp once all previous commands have been
+        // fully executed, independent of the pipeline stage.
+        match query.pool.ty {
+            query::Type::Timestamp => {
+                let name = query.pool.queries[query.id as usize];
+                self.push_cmd(Command::WriteTimestamp(name));
+            }
+            ty => {
+                error!("Query type {:?} can't be used for timestamps", ty);
+                self.cache.error_state = true;
+            }
+        }
     }
 
     unsafe fn push_graphics_constants(
@@ -1342,6 +2316,20 @@ impl command::RawCommandBuffer<Backend> for RawCommandBuffer {
     {
         unimplemented!()
     }
+
+    unsafe fn begin_debug_marker(&mut self, name: &str, _color: u32) {
+        let name_ptr = self.add_raw(name.as_bytes());
+        self.push_cmd(Command::PushDebugGroup(name_ptr));
+    }
+
+    unsafe fn end_debug_marker(&mut self) {
+        self.push_cmd(Command::PopDebugGroup);
+    }
+
+    unsafe fn insert_debug_marker(&mut self, name: &str, _color: u32) {
+        let name_ptr = self.add_raw(name.as_bytes());
+        self.push_cmd(Command::InsertDebugMarker(name_ptr));
+    }
 }
 
 /// Avoids creating second mutable borrows of `self` by requiring mutable
@@ -1373,3 +2361,96 @@ fn push_cmd_internal(
         size: 1,
     });
 }
+
+/// Replace the (front, back) values selected by `faces`.
+fn update_face_values(
+    (mut front, mut back): (pso::StencilValue, pso::StencilValue),
+    faces: pso::Face,
+    value: pso::StencilValue,
+) -> (pso::StencilValue, pso::StencilValue) {
+    if faces.contains(pso::Face::FRONT) {
+        front = value;
+    }
+    if faces.contains(pso::Face::BACK) {
+        back = value;
+    }
+    (front, back)
+}
+
+/// View a single level and layer of an image, as used for framebuffer attachments.
+fn image_layer_view(kind: n::ImageKind, level: image::Level, layer: image::Layer) -> n::ImageView {
+    match kind {
+        n::ImageKind::Surface(s) => n::ImageView::Surface(s),
+        n::ImageKind::Texture(t, target @ gl::TEXTURE_2D)
+        | n::ImageKind::Texture(t, target @ gl::TEXTURE_2D_MULTISAMPLE) => {
+            n::ImageView::Texture(t, target, level, level + 1)
+        }
+        n::ImageKind::Texture(t, target) => n::ImageView::TextureLayer(t, target, level, layer),
+    }
+}
+
+/// Check if `size` bytes starting at `offset` fit into `limit` bytes.
+fn range_fits(offset: u64, size: u64, limit: u64) -> bool {
+    offset.checked_add(size).map_or(false, |end| end <= limit)
+}
+
+/// Check if `size` texels starting at `offset` fit into an image dimension of
+/// `limit` texels.
+fn texel_range_fits(offset: i32, size: u32, limit: u32) -> bool {
+    offset >= 0 && range_fits(offset as u64, size as u64, limit as u64)
+}
+
+/// Check if the `size` bytes starting at `a` and at `b` overlap.
+fn ranges_overlap(a: u64, b: u64, size: u64) -> bool {
+    size != 0 && a < b.saturating_add(size) && b < a.saturating_add(size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{range_fits, ranges_overlap, texel_range_fits};
+    use crate::conv::image_region_size;
+    use crate::hal::format::Format;
+
+    #[test]
+    fn test_range_fits() {
+        assert!(range_fits(0, 16, 16));
+        assert!(range_fits(8, 8, 16));
+        assert!(range_fits(16, 0, 16));
+        assert!(!range_fits(8, 9, 16));
+        assert!(!range_fits(17, 0, 16));
+        assert!(!range_fits(!0, 2, !0));
+    }
+
+    #[test]
+    fn test_texel_range_fits() {
+        assert!(texel_range_fits(0, 16, 16));
+        assert!(texel_range_fits(4, 12, 16));
+        assert!(!texel_range_fits(4, 13, 16));
+        assert!(!texel_range_fits(-1, 1, 16));
+        assert!(!texel_range_fits(1, !0, !0));
+        assert!(!texel_range_fits(i32::max_value(), !0, !0));
+    }
+
+    #[test]
+    fn test_ranges_overlap() {
+        assert!(ranges_overlap(0, 0, 4));
+        assert!(ranges_overlap(0, 3, 4));
+        assert!(ranges_overlap(3, 0, 4));
+        assert!(!ranges_overlap(0, 4, 4));
+        assert!(!ranges_overlap(4, 0, 4));
+        assert!(!ranges_overlap(0, 0, 0));
+        assert!(ranges_overlap(!0 - 1, !0 - 2, 4));
+    }
+
+    #[test]
+    fn test_image_region_size() {
+        assert_eq!(image_region_size(Format::Rgba8Unorm, 4, 4, 1), 64);
+        assert_eq!(image_region_size(Format::R32Sfloat, 3, 2, 5), 120);
+        // Compressed regions are rounded up to whole blocks.
+        assert_eq!(image_region_size(Format::Bc1RgbUnorm, 4, 4, 1), 8);
+        assert_eq!(image_region_size(Format::Bc1RgbUnorm, 5, 1, 1), 16);
+        assert_eq!(image_region_size(Format::Bc3Unorm, 8, 8, 2), 128);
+        assert_eq!(image_region_size(Format::Rgba32Sfloat, !0, !0, !0), !0);
+        assert_eq!(image_region_size(Format::Bc1RgbUnorm, !0, 1, 1), 1 << 33);
+    }
+}
@@ -45,13 +45,30 @@ pub fn wrap_to_gl(w: i::WrapMode) -> t::GLenum {
     }
 }
 
+/// Get the texture target of a view of the given kind.
+pub fn view_kind_to_gl(kind: i::ViewKind, multisampled: bool) -> t::GLenum {
+    match kind {
+        i::ViewKind::D1 => gl::TEXTURE_1D,
+        i::ViewKind::D1Array => gl::TEXTURE_1D_ARRAY,
+        i::ViewKind::D2 if multisampled => gl::TEXTURE_2D_MULTISAMPLE,
+        i::ViewKind::D2 => gl::TEXTURE_2D,
+        i::ViewKind::D2Array if multisampled => gl::TEXTURE_2D_MULTISAMPLE_ARRAY,
+        i::ViewKind::D2Array => gl::TEXTURE_2D_ARRAY,
+        i::ViewKind::D3 => gl::TEXTURE_3D,
+        i::ViewKind::Cube => gl::TEXTURE_CUBE_MAP,
+        i::ViewKind::CubeArray => gl::TEXTURE_CUBE_MAP_ARRAY,
+    }
+}
+
 pub fn buffer_usage_to_gl_target(usage: buffer::Usage) -> Option<t::GLenum> {
     use self::buffer::Usage;
-    match usage & (Usage::UNIFORM | Usage::INDEX | Usage::VERTEX | Usage::INDIRECT) {
+    match usage & (Usage::UNIFORM | Usage::STORAGE | Usage::INDEX | Usage::VERTEX | Usage::INDIRECT)
+    {
         Usage::UNIFORM => Some(gl::UNIFORM_BUFFER),
+        Usage::STORAGE => Some(gl::SHADER_STORAGE_BUFFER),
         Usage::INDEX => Some(gl::ELEMENT_ARRAY_BUFFER),
         Usage::VERTEX => Some(gl::ARRAY_BUFFER),
-        Usage::INDIRECT => unimplemented!(),
+        Usage::INDIRECT => Some(gl::DRAW_INDIRECT_BUFFER),
         _ => None,
     }
 }
@@ -71,6 +88,167 @@ pub fn primitive_to_gl_primitive(primitive: Primitive) -> t::GLenum {
     }
 }
 
+// S3TC formats from `GL_EXT_texture_compression_s3tc` and `GL_EXT_texture_sRGB`,
+// which are not part of the generated bindings.
+const COMPRESSED_RGB_S3TC_DXT1_EXT: t::GLenum = 0x83F0;
+const COMPRESSED_RGBA_S3TC_DXT1_EXT: t::GLenum = 0x83F1;
+const COMPRESSED_RGBA_S3TC_DXT3_EXT: t::GLenum = 0x83F2;
+const COMPRESSED_RGBA_S3TC_DXT5_EXT: t::GLenum = 0x83F3;
+const COMPRESSED_SRGB_S3TC_DXT1_EXT: t::GLenum = 0x8C4C;
+const COMPRESSED_SRGB_ALPHA_S3TC_DXT1_EXT: t::GLenum = 0x8C4D;
+const COMPRESSED_SRGB_ALPHA_S3TC_DXT3_EXT: t::GLenum = 0x8C4E;
+const COMPRESSED_SRGB_ALPHA_S3TC_DXT5_EXT: t::GLenum = 0x8C4F;
+
+/// Get the internal format, pixel format and pixel type used for storing
+/// images of the given format.
+///
+/// Compressed formats are uploaded as raw blocks, only the internal format
+/// is meaningful for them.
+pub fn image_format_to_gl(format: Format) -> Option<(t::GLenum, t::GLenum, t::GLenum)> {
+    use crate::hal::format::Format::*;
+    let format = match format {
+        Rgb8Unorm => (gl::RGB8, gl::RGB, gl::UNSIGNED_BYTE),
+        Rgb8Srgb => (gl::SRGB8, gl::RGB, gl::UNSIGNED_BYTE),
+        Rgba8Unorm => (gl::RGBA8, gl::RGBA, gl::UNSIGNED_BYTE),
+        Rgba8Srgb => (gl::SRGB8_ALPHA8, gl::RGBA, gl::UNSIGNED_BYTE),
+        Bgra8Unorm => (gl::RGBA8, gl::BGRA, gl::UNSIGNED_BYTE),
+        Bgra8Srgb => (gl::SRGB8_ALPHA8, gl::BGRA, gl::UNSIGNED_BYTE),
+        A2r10g10b10Unorm => (gl::RGB10_A2, gl::BGRA, gl::UNSIGNED_INT_2_10_10_10_REV),
+        A2b10g10r10Unorm => (gl::RGB10_A2, gl::RGBA, gl::UNSIGNED_INT_2_10_10_10_REV),
+        A2b10g10r10Uint => (
+            gl::RGB10_A2UI,
+            gl::RGBA_INTEGER,
+            gl::UNSIGNED_INT_2_10_10_10_REV,
+        ),
+        R16Sfloat => (gl::R16F, gl::RED, gl::HALF_FLOAT),
+        Rg16Sfloat => (gl::RG16F, gl::RG, gl::HALF_FLOAT),
+        Rgba16Sfloat => (gl::RGBA16F, gl::RGBA, gl::HALF_FLOAT),
+        R32Sfloat => (gl::R32F, gl::RED, gl::FLOAT),
+        Rg32Sfloat => (gl::RG32F, gl::RG, gl::FLOAT),
+        Rgba32Sfloat => (gl::RGBA32F, gl::RGBA, gl::FLOAT),
+        R8Uint => (gl::R8UI, gl::RED_INTEGER, gl::UNSIGNED_BYTE),
+        R8Sint => (gl::R8I, gl::RED_INTEGER, gl::BYTE),
+        Rg8Uint => (gl::RG8UI, gl::RG_INTEGER, gl::UNSIGNED_BYTE),
+        Rg8Sint => (gl::RG8I, gl::RG_INTEGER, gl::BYTE),
+        Rgba8Uint => (gl::RGBA8UI, gl::RGBA_INTEGER, gl::UNSIGNED_BYTE),
+        Rgba8Sint => (gl::RGBA8I, gl::RGBA_INTEGER, gl::BYTE),
+        R16Uint => (gl::R16UI, gl::RED_INTEGER, gl::UNSIGNED_SHORT),
+        R16Sint => (gl::R16I, gl::RED_INTEGER, gl::SHORT),
+        Rg16Uint => (gl::RG16UI, gl::RG_INTEGER, gl::UNSIGNED_SHORT),
+        Rg16Sint => (gl::RG16I, gl::RG_INTEGER, gl::SHORT),
+        Rgba16Uint => (gl::RGBA16UI, gl::RGBA_INTEGER, gl::UNSIGNED_SHORT),
+        Rgba16Sint => (gl::RGBA16I, gl::RGBA_INTEGER, gl::SHORT),
+        R32Uint => (gl::R32UI, gl::RED_INTEGER, gl::UNSIGNED_INT),
+        R32Sint => (gl::R32I, gl::RED_INTEGER, gl::INT),
+        Rg32Uint => (gl::RG32UI, gl::RG_INTEGER, gl::UNSIGNED_INT),
+        Rg32Sint => (gl::RG32I, gl::RG_INTEGER, gl::INT),
+        Rgba32Uint => (gl::RGBA32UI, gl::RGBA_INTEGER, gl::UNSIGNED_INT),
+        Rgba32Sint => (gl::RGBA32I, gl::RGBA_INTEGER, gl::INT),
+        B10g11r11Ufloat => (
+            gl::R11F_G11F_B10F,
+            gl::RGB,
+            gl::UNSIGNED_INT_10F_11F_11F_REV,
+        ),
+        Bc1RgbUnorm => (COMPRESSED_RGB_S3TC_DXT1_EXT, gl::RGB, gl::UNSIGNED_BYTE),
+        Bc1RgbSrgb => (COMPRESSED_SRGB_S3TC_DXT1_EXT, gl::RGB, gl::UNSIGNED_BYTE),
+        Bc1RgbaUnorm => (COMPRESSED_RGBA_S3TC_DXT1_EXT, gl::RGBA, gl::UNSIGNED_BYTE),
+        Bc1RgbaSrgb => (
+            COMPRESSED_SRGB_ALPHA_S3TC_DXT1_EXT,
+            gl::RGBA,
+            gl::UNSIGNED_BYTE,
+        ),
+        Bc2Unorm => (COMPRESSED_RGBA_S3TC_DXT3_EXT, gl::RGBA, gl::UNSIGNED_BYTE),
+        Bc2Srgb => (
+            COMPRESSED_SRGB_ALPHA_S3TC_DXT3_EXT,
+            gl::RGBA,
+            gl::UNSIGNED_BYTE,
+        ),
+        Bc3Unorm => (COMPRESSED_RGBA_S3TC_DXT5_EXT, gl::RGBA, gl::UNSIGNED_BYTE),
+        Bc3Srgb => (
+            COMPRESSED_SRGB_ALPHA_S3TC_DXT5_EXT,
+            gl::RGBA,
+            gl::UNSIGNED_BYTE,
+        ),
+        Bc4Unorm => (gl::COMPRESSED_RED_RGTC1, gl::RED, gl::UNSIGNED_BYTE),
+        Bc4Snorm => (gl::COMPRESSED_SIGNED_RED_RGTC1, gl::RED, gl::BYTE),
+        Bc5Unorm => (gl::COMPRESSED_RG_RGTC2, gl::RG, gl::UNSIGNED_BYTE),
+        Bc5Snorm => (gl::COMPRESSED_SIGNED_RG_RGTC2, gl::RG, gl::BYTE),
+        Bc6hUfloat => (gl::COMPRESSED_RGB_BPTC_UNSIGNED_FLOAT, gl::RGB, gl::FLOAT),
+        Bc6hSfloat => (gl::COMPRESSED_RGB_BPTC_SIGNED_FLOAT, gl::RGB, gl::FLOAT),
+        Bc7Unorm => (gl::COMPRESSED_RGBA_BPTC_UNORM, gl::RGBA, gl::UNSIGNED_BYTE),
+        Bc7Srgb => (
+            gl::COMPRESSED_SRGB_ALPHA_BPTC_UNORM,
+            gl::RGBA,
+            gl::UNSIGNED_BYTE,
+        ),
+        Etc2R8g8b8Unorm => (gl::COMPRESSED_RGB8_ETC2, gl::RGB, gl::UNSIGNED_BYTE),
+        Etc2R8g8b8Srgb => (gl::COMPRESSED_SRGB8_ETC2, gl::RGB, gl::UNSIGNED_BYTE),
+        Etc2R8g8b8a1Unorm => (
+            gl::COMPRESSED_RGB8_PUNCHTHROUGH_ALPHA1_ETC2,
+            gl::RGBA,
+            gl::UNSIGNED_BYTE,
+        ),
+        Etc2R8g8b8a1Srgb => (
+            gl::COMPRESSED_SRGB8_PUNCHTHROUGH_ALPHA1_ETC2,
+            gl::RGBA,
+            gl::UNSIGNED_BYTE,
+        ),
+        Etc2R8g8b8a8Unorm => (gl::COMPRESSED_RGBA8_ETC2_EAC, gl::RGBA, gl::UNSIGNED_BYTE),
+        Etc2R8g8b8a8Srgb => (
+            gl::COMPRESSED_SRGB8_ALPHA8_ETC2_EAC,
+            gl::RGBA,
+            gl::UNSIGNED_BYTE,
+        ),
+        EacR11Unorm => (gl::COMPRESSED_R11_EAC, gl::RED, gl::UNSIGNED_BYTE),
+        EacR11Snorm => (gl::COMPRESSED_SIGNED_R11_EAC, gl::RED, gl::BYTE),
+        EacR11g11Unorm => (gl::COMPRESSED_RG11_EAC, gl::RG, gl::UNSIGNED_BYTE),
+        EacR11g11Snorm => (gl::COMPRESSED_SIGNED_RG11_EAC, gl::RG, gl::BYTE),
+        S8Uint => (gl::STENCIL_INDEX8, gl::STENCIL_INDEX, gl::UNSIGNED_BYTE),
+        D16Unorm => (
+            gl::DEPTH_COMPONENT16,
+            gl::DEPTH_COMPONENT,
+            gl::UNSIGNED_SHORT,
+        ),
+        X8D24Unorm => (gl::DEPTH_COMPONENT24, gl::DEPTH_COMPONENT, gl::UNSIGNED_INT),
+        D32Sfloat => (gl::DEPTH_COMPONENT32F, gl::DEPTH_COMPONENT, gl::FLOAT),
+        D24UnormS8Uint => (
+            gl::DEPTH24_STENCIL8,
+            gl::DEPTH_STENCIL,
+            gl::UNSIGNED_INT_24_8,
+        ),
+        D32SfloatS8Uint => (
+            gl::DEPTH32F_STENCIL8,
+            gl::DEPTH_STENCIL,
+            gl::FLOAT_32_UNSIGNED_INT_24_8_REV,
+        ),
+        _ => return None,
+    };
+
+    Some(format)
+}
+
+/// Return the target addressing a single layer of a texture, which differs
+/// from the texture target for the faces of cube maps.
+pub fn texture_layer_target(target: t::GLenum, layer: i::Layer) -> t::GLenum {
+    if target == gl::TEXTURE_CUBE_MAP {
+        gl::TEXTURE_CUBE_MAP_POSITIVE_X + layer as t::GLenum
+    } else {
+        target
+    }
+}
+
+/// Size in bytes of an image region of the given format, rounded up to whole
+/// texel blocks. Saturates at `u64::MAX` instead of overflowing.
+pub fn image_region_size(format: Format, width: u32, height: u32, depth: u32) -> u64 {
+    let desc = format.surface_desc();
+    let (block_width, block_height) = (desc.dim.0 as u64, desc.dim.1 as u64);
+    let blocks_x = (width as u64 + block_width - 1) / block_width;
+    let blocks_y = (height as u64 + block_height - 1) / block_height;
+    (blocks_x * blocks_y)
+        .saturating_mul(depth as u64)
+        .saturating_mul((desc.bits / 8) as u64)
+}
+
 pub fn format_to_gl_format(
     format: Format,
 ) -> Option<(gl::types::GLint, gl::types::GLenum, VertexAttribFunction)> {
@@ -80,18 +258,30 @@ pub fn format_to_gl_format(
     let _ = Double; //mark as used
                     // TODO: Add more formats and error handling for `None`
     let format = match format {
+        R8Unorm => (1, UNSIGNED_BYTE, Normalized),
+        R8Snorm => (1, BYTE, Normalized),
         R8Uint => (1, UNSIGNED_BYTE, Integer),
         R8Sint => (1, BYTE, Integer),
+        Rg8Unorm => (2, UNSIGNED_BYTE, Normalized),
+        Rg8Snorm => (2, BYTE, Normalized),
         Rg8Uint => (2, UNSIGNED_BYTE, Integer),
         Rg8Sint => (2, BYTE, Integer),
+        Rgba8Unorm => (4, UNSIGNED_BYTE, Normalized),
+        Rgba8Snorm => (4, BYTE, Normalized),
         Rgba8Uint => (4, UNSIGNED_BYTE, Integer),
         Rgba8Sint => (4, BYTE, Integer),
+        R16Unorm => (1, UNSIGNED_SHORT, Normalized),
+        R16Snorm => (1, SHORT, Normalized),
         R16Uint => (1, UNSIGNED_SHORT, Integer),
         R16Sint => (1, SHORT, Integer),
         R16Sfloat => (1, HALF_FLOAT, Float),
+        Rg16Unorm => (2, UNSIGNED_SHORT, Normalized),
+        Rg16Snorm => (2, SHORT, Normalized),
         Rg16Uint => (2, UNSIGNED_SHORT, Integer),
         Rg16Sint => (2, SHORT, Integer),
         Rg16Sfloat => (2, HALF_FLOAT, Float),
+        Rgba16Unorm => (4, UNSIGNED_SHORT, Normalized),
+        Rgba16Snorm => (4, SHORT, Normalized),
         Rgba16Uint => (4, UNSIGNED_SHORT, Integer),
         Rgba16Sint => (4, SHORT, Integer),
         Rgba16Sfloat => (4, HALF_FLOAT, Float),
@@ -9,7 +9,7 @@ use crate::gl::types::{GLenum, GLfloat, GLint};
 use crate::{gl, GlContainer};
 
 use crate::hal::backend::FastHashMap;
-use crate::hal::format::{Format, Swizzle};
+use crate::hal::format::{ChannelType, Format, Swizzle};
 use crate::hal::pool::CommandPoolCreateFlags;
 use crate::hal::queue::QueueFamilyId;
 use crate::hal::range::RangeArg;
@@ -118,6 +118,17 @@ impl Device {
         Device { share: share }
     }
 
+    unsafe fn set_object_label(&self, identifier: GLenum, name: gl::types::GLuint, label: &str) {
+        if self.share.private_caps.object_labels {
+            self.share.context.ObjectLabel(
+                identifier,
+                name,
+                label.len() as _,
+                label.as_ptr() as *const _,
+            );
+        }
+    }
+
     pub fn create_shader_module_from_source(
         &self,
         data: &[u8],
@@ -126,12 +137,16 @@ impl Device {
         let gl = &self.share.context;
 
         let can_compute = self.share.limits.max_compute_work_group_count[0] != 0;
-        let can_tessellate = self.share.limits.max_patch_size != 0;
+        let can_tessellate = self
+            .share
+            .features
+            .contains(hal::Features::TESSELLATION_SHADER);
+        let can_geometry = self.share.features.contains(hal::Features::GEOMETRY_SHADER);
         let target = match stage {
             pso::Stage::Vertex => gl::VERTEX_SHADER,
             pso::Stage::Hull if can_tessellate => gl::TESS_CONTROL_SHADER,
             pso::Stage::Domain if can_tessellate => gl::TESS_EVALUATION_SHADER,
-            pso::Stage::Geometry => gl::GEOMETRY_SHADER,
+            pso::Stage::Geometry if can_geometry => gl::GEOMETRY_SHADER,
             pso::Stage::Fragment => gl::FRAGMENT_SHADER,
             pso::Stage::Compute if can_compute => gl::COMPUTE_SHADER,
             _ => return Err(d::ShaderError::UnsupportedStage(stage)),
@@ -160,7 +175,12 @@ impl Device {
             }
             Ok(n::ShaderModule::Raw(name))
         } else {
-            Err(d::ShaderError::CompilationFailed(log))
+            unsafe { gl.DeleteShader(name) };
+            // Driver logs refer to source lines, name the stage they belong to.
+            Err(d::ShaderError::CompilationFailed(format!(
+                "{} shader: {}",
+                stage, log
+            )))
         }
     }
 
@@ -174,20 +194,22 @@ impl Device {
             n::ImageView::Surface(surface) => unsafe {
                 gl.FramebufferRenderbuffer(point, attachment, gl::RENDERBUFFER, surface);
             },
-            n::ImageView::Texture(texture, level) => unsafe {
-                gl.BindTexture(gl::TEXTURE_2D, texture);
-                gl.FramebufferTexture2D(point, attachment, gl::TEXTURE_2D, texture, level as _);
+            n::ImageView::Texture(texture, target, level, _) => unsafe {
+                gl.BindTexture(target, texture);
+                gl.FramebufferTexture2D(point, attachment, target, texture, level as _);
             },
-            n::ImageView::TextureLayer(texture, level, layer) => unsafe {
-                gl.BindTexture(gl::TEXTURE_2D, texture);
-                gl.FramebufferTexture3D(
-                    point,
-                    attachment,
-                    gl::TEXTURE_2D,
-                    texture,
-                    level as _,
-                    layer as _,
-                );
+            n::ImageView::TextureLayer(texture, gl::TEXTURE_CUBE_MAP, level, layer) => unsafe {
+                let face = conv::texture_layer_target(gl::TEXTURE_CUBE_MAP, layer);
+                gl.BindTexture(gl::TEXTURE_CUBE_MAP, texture);
+                gl.FramebufferTexture2D(point, attachment, face, texture, level as _);
+            },
+            n::ImageView::TextureLayer(texture, target, level, layer) => unsafe {
+                gl.BindTexture(target, texture);
+                gl.FramebufferTexture3D(point, attachment, target, texture, level as _, layer as _);
+            },
+            n::ImageView::View(texture, target) => unsafe {
+                gl.BindTexture(target, texture);
+                gl.FramebufferTexture2D(point, attachment, target, texture, 0);
             },
         }
     }
@@ -197,12 +219,19 @@ impl Device {
             n::ImageView::Surface(surface) => unsafe {
                 gl.FramebufferRenderbuffer(point, attachment, gl::RENDERBUFFER, surface);
             },
-            n::ImageView::Texture(texture, level) => unsafe {
+            n::ImageView::Texture(texture, _, level, _) => unsafe {
                 gl.FramebufferTexture(point, attachment, texture, level as _);
             },
-            n::ImageView::TextureLayer(texture, level, layer) => unsafe {
+            n::ImageView::TextureLayer(texture, gl::TEXTURE_CUBE_MAP, level, layer) => unsafe {
+                let face = conv::texture_layer_target(gl::TEXTURE_CUBE_MAP, layer);
+                gl.FramebufferTexture2D(point, attachment, face, texture, level as _);
+            },
+            n::ImageView::TextureLayer(texture, _, level, layer) => unsafe {
                 gl.FramebufferTextureLayer(point, attachment, texture, level as _, layer as _);
             },
+            n::ImageView::View(texture, _) => unsafe {
+                gl.FramebufferTexture(point, attachment, texture, 0);
+            },
         }
     }
 
@@ -300,6 +329,44 @@ impl Device {
         })
     }
 
+    /// Query the internal format of a texture level, as required to bind
+    /// it to an image unit.
+    fn texture_internal_format(
+        &self,
+        texture: n::Texture,
+        target: n::TextureTarget,
+        level: i::Level,
+    ) -> gl::types::GLenum {
+        let gl = &self.share.context;
+        let mut format = 0;
+        unsafe {
+            gl.BindTexture(target, texture);
+            gl.GetTexLevelParameteriv(target, level as _, gl::TEXTURE_INTERNAL_FORMAT, &mut format);
+        }
+        format as _
+    }
+
+    /// Levels to restrict a sampled texture view to when it is bound.
+    ///
+    /// Views over sub-ranges are texture views where those are supported,
+    /// otherwise the base and maximum level of the texture is set on every
+    /// bind, as several views of the same texture may be in use.
+    fn sampled_levels(
+        &self,
+        target: n::TextureTarget,
+        start: i::Level,
+        end: i::Level,
+    ) -> Option<(i::Level, i::Level)> {
+        let caps = &self.share.private_caps;
+        let multisampled =
+            target == gl::TEXTURE_2D_MULTISAMPLE || target == gl::TEXTURE_2D_MULTISAMPLE_ARRAY;
+        if (caps.texture_view && caps.image_storage) || multisampled {
+            None
+        } else {
+            Some((start, end))
+        }
+    }
+
     fn remap_bindings(
         &self,
         ast: &mut spirv::Ast<glsl::Target>,
@@ -321,6 +388,20 @@ impl Device {
             &res.uniform_buffers,
             n::BindingTypes::UniformBuffers,
         );
+        self.remap_binding(
+            ast,
+            desc_remap_data,
+            nb_map,
+            &res.storage_buffers,
+            n::BindingTypes::StorageBuffers,
+        );
+        self.remap_binding(
+            ast,
+            desc_remap_data,
+            nb_map,
+            &res.storage_images,
+            n::BindingTypes::StorageImages,
+        );
     }
 
     fn remap_binding(
@@ -428,41 +509,74 @@ impl Device {
         stage: pso::Stage,
         desc_remap_data: &mut n::DescRemapData,
         name_binding_map: &mut FastHashMap<String, pso::DescriptorBinding>,
-    ) -> n::Shader {
+    ) -> Result<n::Shader, d::ShaderError> {
         assert_eq!(point.entry, "main");
         match *point.module {
             n::ShaderModule::Raw(raw) => {
                 debug!("Can't remap bindings for raw shaders. Assuming they are already rebound.");
-                raw
+                Ok(raw)
             }
             n::ShaderModule::Spirv(ref spirv) => {
-                let mut ast = self.parse_spirv(spirv).unwrap();
+                let mut ast = self.parse_spirv(spirv)?;
 
-                self.specialize_ast(&mut ast, &point.specialization).unwrap();
+                self.specialize_ast(&mut ast, &point.specialization)?;
                 self.remap_bindings(&mut ast, desc_remap_data, name_binding_map);
                 self.combine_separate_images_and_samplers(
                     &mut ast,
                     desc_remap_data,
                     name_binding_map,
                 );
-                self.set_push_const_layout(&mut ast).unwrap();
+                self.set_push_const_layout(&mut ast)?;
 
-                let glsl = self.translate_spirv(&mut ast).unwrap();
+                let glsl = self.translate_spirv(&mut ast)?;
                 debug!("SPIRV-Cross generated shader:\n{}", glsl);
-                let shader = match self
+                let module = self
                     .create_shader_module_from_source(glsl.as_bytes(), stage)
-                    .unwrap()
-                {
+                    .map_err(|err| match err {
+                        // The driver log refers to lines of the generated GLSL,
+                        // which is included so they can be matched up.
+                        d::ShaderError::CompilationFailed(log) => {
+                            d::ShaderError::CompilationFailed(format!(
+                                "{}\nGenerated GLSL:\n{}",
+                                log,
+                                numbered_lines(&glsl)
+                            ))
+                        }
+                        err => err,
+                    })?;
+                let shader = match module {
                     n::ShaderModule::Raw(raw) => raw,
                     _ => panic!("Unhandled"),
                 };
 
-                shader
+                Ok(shader)
             }
         }
     }
 }
 
+/// Prefix every line of a shader source with its line number, starting at 1
+/// like the line numbers of driver logs.
+fn numbered_lines(source: &str) -> String {
+    source
+        .lines()
+        .enumerate()
+        .map(|(i, line)| format!("{:4}: {}\n", i + 1, line))
+        .collect()
+}
+
+/// Clamp the requested anisotropy to the maximum reported by the driver.
+fn clamp_anisotropy(requested: u8, max: GLfloat) -> GLfloat {
+    let requested = requested as GLfloat;
+    if requested > max {
+        debug!(
+            "Clamping anisotropy {} to the supported maximum {}",
+            requested, max
+        );
+    }
+    requested.min(max).max(1.0)
+}
+
 pub(crate) unsafe fn set_sampler_info<SetParamFloat, SetParamFloatVec, SetParamInt>(
     share: &Starc<Share>,
     info: &i::SamplerInfo,
@@ -477,10 +591,13 @@ pub(crate) unsafe fn set_sampler_info<SetParamFloat, SetParamFloatVec, SetParamI
     let (min, mag) = conv::filter_to_gl(info.mag_filter, info.min_filter, info.mip_filter);
     match info.anisotropic {
         i::Anisotropic::On(fac) if fac > 1 => {
+            let max = share.limits.max_sampler_anisotropy;
+            let fac = clamp_anisotropy(fac, max);
+            // Samplers requesting anisotropy without support are rejected on creation.
             if share.private_caps.sampler_anisotropy_ext {
-                set_param_float(gl::TEXTURE_MAX_ANISOTROPY_EXT, fac as GLfloat);
+                set_param_float(gl::TEXTURE_MAX_ANISOTROPY_EXT, fac);
             } else if share.features.contains(c::Features::SAMPLER_ANISOTROPY) {
-                set_param_float(gl::TEXTURE_MAX_ANISOTROPY_EXT, fac as GLfloat);
+                set_param_float(gl::TEXTURE_MAX_ANISOTROPY_EXT, fac);
             }
         }
         _ => (),
@@ -489,18 +606,25 @@ pub(crate) unsafe fn set_sampler_info<SetParamFloat, SetParamFloatVec, SetParamI
     set_param_int(gl::TEXTURE_MIN_FILTER, min as GLint);
     set_param_int(gl::TEXTURE_MAG_FILTER, mag as GLint);
 
+    let border_color = share
+        .legacy_features
+        .contains(LegacyFeatures::SAMPLER_BORDER_COLOR);
+    let wrap = |mode| match mode {
+        i::WrapMode::Border if !border_color => {
+            warn!("Border wrap mode is not supported, clamping to the edge instead");
+            gl::CLAMP_TO_EDGE as GLint
+        }
+        _ => conv::wrap_to_gl(mode) as GLint,
+    };
     let (s, t, r) = info.wrap_mode;
-    set_param_int(gl::TEXTURE_WRAP_S, conv::wrap_to_gl(s) as GLint);
-    set_param_int(gl::TEXTURE_WRAP_T, conv::wrap_to_gl(t) as GLint);
-    set_param_int(gl::TEXTURE_WRAP_R, conv::wrap_to_gl(r) as GLint);
+    set_param_int(gl::TEXTURE_WRAP_S, wrap(s));
+    set_param_int(gl::TEXTURE_WRAP_T, wrap(t));
+    set_param_int(gl::TEXTURE_WRAP_R, wrap(r));
 
     if share.features.contains(hal::Features::SAMPLER_MIP_LOD_BIAS) {
         set_param_float(gl::TEXTURE_LOD_BIAS, info.lod_bias.into());
     }
-    if share
-        .legacy_features
-        .contains(LegacyFeatures::SAMPLER_BORDER_COLOR)
-    {
+    if border_color {
         let border: [f32; 4] = info.border.into();
         set_param_float_vec(gl::TEXTURE_BORDER_COLOR, &border);
     }
@@ -543,6 +667,7 @@ impl d::Device<B> for Device {
         flags: CommandPoolCreateFlags,
     ) -> Result<RawCommandPool, d::OutOfMemory> {
         let fbo = create_fbo_internal(&self.share);
+        let blit_fbo = create_fbo_internal(&self.share);
         let limits = self.share.limits.into();
         let memory = if flags.contains(CommandPoolCreateFlags::RESET_INDIVIDUAL) {
             BufferMemory::Individual {
@@ -557,15 +682,16 @@ impl d::Device<B> for Device {
 
         Ok(RawCommandPool {
             fbo,
+            blit_fbo,
             limits,
             memory: Arc::new(Mutex::new(memory)),
         })
     }
 
     unsafe fn destroy_command_pool(&self, pool: RawCommandPool) {
-        if let Some(fbo) = pool.fbo {
-            let gl = &self.share.context;
-            gl.DeleteFramebuffers(1, &fbo);
+        let gl = &self.share.context;
+        for fbo in pool.fbo.iter().chain(pool.blit_fbo.iter()) {
+            gl.DeleteFramebuffers(1, fbo);
         }
     }
 
@@ -652,9 +778,22 @@ impl d::Device<B> for Device {
                             binding.binding,
                         );
                     }
-                    StorageImage | UniformTexelBuffer | UniformBufferDynamic
-                    | StorageTexelBuffer | StorageBufferDynamic | StorageBuffer
-                    | InputAttachment => unimplemented!(), // 6
+                    StorageBuffer => {
+                        drd.insert_missing_binding_into_spare(
+                            n::BindingTypes::StorageBuffers,
+                            set as _,
+                            binding.binding,
+                        );
+                    }
+                    StorageImage => {
+                        drd.insert_missing_binding_into_spare(
+                            n::BindingTypes::StorageImages,
+                            set as _,
+                            binding.binding,
+                        );
+                    }
+                    UniformTexelBuffer | UniformBufferDynamic | StorageTexelBuffer
+                    | StorageBufferDynamic | InputAttachment => unimplemented!(),
                 }
             })
         });
@@ -702,6 +841,94 @@ impl d::Device<B> for Device {
             }
         };
 
+        let targets = &desc.blender.targets;
+        if targets.iter().any(|t| t.1.is_dual_source())
+            && (targets.len() > 1 || !share.features.contains(hal::Features::DUAL_SRC_BLENDING))
+        {
+            return Err(pso::CreationError::DualSourceBlending);
+        }
+        if targets.iter().any(|t| *t != targets[0])
+            && !share.features.contains(hal::Features::INDEPENDENT_BLENDING)
+        {
+            error!("Independent blending is not supported, all color targets must blend alike");
+            return Err(pso::CreationError::Other);
+        }
+        for (target, &at_id) in targets.iter().zip(&subpass.color_attachments) {
+            let format = desc.subpass.main_pass.attachments[at_id].format;
+            match format.map(|f| f.base_format().1) {
+                Some(ChannelType::Uint) | Some(ChannelType::Sint)
+                    if target.1 != pso::BlendState::Off =>
+                {
+                    error!("Blending is not supported on {:?} attachments", format);
+                    return Err(pso::CreationError::Other);
+                }
+                _ => {}
+            }
+        }
+        match desc.input_assembler.primitive {
+            c::Primitive::LineListAdjacency
+            | c::Primitive::LineStripAdjacency
+            | c::Primitive::TriangleListAdjacency
+            | c::Primitive::TriangleStripAdjacency
+                if !share.features.contains(hal::Features::GEOMETRY_SHADER) =>
+            {
+                error!(
+                    "Primitive {:?} requires geometry shader support",
+                    desc.input_assembler.primitive
+                );
+                return Err(pso::CreationError::Other);
+            }
+            c::Primitive::PatchList(_)
+                if !share.features.contains(hal::Features::TESSELLATION_SHADER) =>
+            {
+                error!("Patch lists require tessellation shader support");
+                return Err(pso::CreationError::Other);
+            }
+            c::Primitive::PatchList(size) if size > share.limits.max_patch_size => {
+                error!(
+                    "Patch size {} exceeds the supported maximum of {}",
+                    size, share.limits.max_patch_size
+                );
+                return Err(pso::CreationError::Other);
+            }
+            _ => {}
+        }
+        if desc.rasterizer.polygon_mode != pso::PolygonMode::Fill
+            && !share
+                .features
+                .contains(hal::Features::NON_FILL_POLYGON_MODE)
+        {
+            error!(
+                "Polygon mode {:?} is not supported",
+                desc.rasterizer.polygon_mode
+            );
+            return Err(pso::CreationError::Other);
+        }
+        if let pso::PolygonMode::Line(width) = desc.rasterizer.polygon_mode {
+            if width != 1.0 && !share.features.contains(hal::Features::LINE_WIDTH) {
+                warn!("Wide lines are not supported");
+            }
+        }
+        if let Some(ref ms) = desc.multisampling {
+            if ms.alpha_coverage && ms.rasterization_samples <= 1 {
+                warn!("Alpha to coverage has no effect without multisampling");
+            }
+            if ms.sample_shading.is_some()
+                && !share.features.contains(hal::Features::SAMPLE_RATE_SHADING)
+            {
+                error!("Sample rate shading is not supported");
+                return Err(pso::CreationError::Other);
+            }
+            if ms.sample_mask != !0 && !share.private_caps.sample_mask {
+                warn!("Sample masks are not supported");
+            }
+        }
+        if let Some(pso::State::Static(ref bias)) = desc.rasterizer.depth_bias {
+            if bias.clamp != 0.0 {
+                warn!("Depth bias clamping is not supported");
+            }
+        }
+
         let program = {
             let name = gl.CreateProgram();
 
@@ -715,23 +942,32 @@ impl d::Device<B> for Device {
             ];
 
             let mut name_binding_map = FastHashMap::<String, pso::DescriptorBinding>::default();
-            let shader_names = &shaders
-                .iter()
-                .filter_map(|&(stage, point_maybe)| {
-                    point_maybe.map(|point| {
-                        let shader_name = self.compile_shader(
-                            point,
-                            stage,
-                            &mut desc.layout.desc_remap_data.write().unwrap(),
-                            &mut name_binding_map,
-                        );
-
+            let mut shader_names = Vec::with_capacity(shaders.len());
+            for &(stage, point_maybe) in &shaders {
+                let point = match point_maybe {
+                    Some(point) => point,
+                    None => continue,
+                };
+                let result = self.compile_shader(
+                    point,
+                    stage,
+                    &mut desc.layout.desc_remap_data.write().unwrap(),
+                    &mut name_binding_map,
+                );
+                match result {
+                    Ok(shader_name) => {
                         gl.AttachShader(name, shader_name);
-
-                        shader_name
-                    })
-                })
-                .collect::<Vec<_>>();
+                        shader_names.push(shader_name);
+                    }
+                    Err(err) => {
+                        for shader_name in shader_names {
+                            gl.DeleteShader(shader_name);
+                        }
+                        gl.DeleteProgram(name);
+                        return Err(pso::CreationError::Shader(err));
+                    }
+                }
+            }
 
             if !share.private_caps.program_interface && share.private_caps.frag_data_location {
                 for i in 0..subpass.color_attachments.len() {
@@ -751,8 +987,8 @@ impl d::Device<B> for Device {
             }
 
             for shader_name in shader_names {
-                gl.DetachShader(name, *shader_name);
-                gl.DeleteShader(*shader_name);
+                gl.DetachShader(name, shader_name);
+                gl.DeleteShader(shader_name);
             }
 
             if !self
@@ -775,6 +1011,7 @@ impl d::Device<B> for Device {
                     warn!("\tLog: {}", log);
                 }
             } else {
+                gl.DeleteProgram(name);
                 return Err(pso::CreationError::Shader(
                     d::ShaderError::CompilationFailed(log),
                 ));
@@ -849,6 +1086,7 @@ impl d::Device<B> for Device {
             program,
             primitive: conv::primitive_to_gl_primitive(desc.input_assembler.primitive),
             patch_size,
+            primitive_restart: desc.input_assembler.primitive_restart,
             blend_targets: desc.blender.targets.clone(),
             vertex_buffers,
             attributes: desc
@@ -870,6 +1108,9 @@ impl d::Device<B> for Device {
             uniforms,
             rasterizer: desc.rasterizer,
             depth: desc.depth_stencil.depth,
+            stencil: desc.depth_stencil.stencil,
+            multisampling: desc.multisampling.clone(),
+            baked_states: desc.baked_states.clone(),
         })
     }
 
@@ -881,6 +1122,12 @@ impl d::Device<B> for Device {
         let gl = &self.share.context;
         let share = &self.share;
 
+        // The work group limits are only queried when compute shaders are available.
+        if share.limits.max_compute_work_group_count[0] == 0 {
+            error!("Compute shaders require GL 4.3, GLES 3.1 or GL_ARB_compute_shader");
+            return Err(pso::CreationError::Other);
+        }
+
         let program = {
             let name = gl.CreateProgram();
 
@@ -891,6 +1138,13 @@ impl d::Device<B> for Device {
                 &mut desc.layout.desc_remap_data.write().unwrap(),
                 &mut name_binding_map,
             );
+            let shader = match shader {
+                Ok(shader) => shader,
+                Err(err) => {
+                    gl.DeleteProgram(name);
+                    return Err(pso::CreationError::Shader(err));
+                }
+            };
             gl.AttachShader(name, shader);
 
             gl.LinkProgram(name);
@@ -922,7 +1176,10 @@ impl d::Device<B> for Device {
                     warn!("\tLog: {}", log);
                 }
             } else {
-                return Err(pso::CreationError::Other);
+                gl.DeleteProgram(name);
+                return Err(pso::CreationError::Shader(
+                    d::ShaderError::CompilationFailed(log),
+                ));
             }
 
             name
@@ -954,10 +1211,10 @@ impl d::Device<B> for Device {
         let mut render_attachments = Vec::with_capacity(pass.attachments.len());
         let mut color_attachment_index = 0;
         for attachment in &pass.attachments {
-            if color_attachment_index > self.share.limits.framebuffer_color_samples_count as _ {
+            if color_attachment_index >= self.share.limits.max_color_attachments as _ {
                 panic!(
                     "Invalid number of color attachments: {} color_attachment of {}",
-                    color_attachment_index, self.share.limits.framebuffer_color_samples_count
+                    color_attachment_index, self.share.limits.max_color_attachments
                 );
             }
 
@@ -967,15 +1224,17 @@ impl d::Device<B> for Device {
             };
 
             match attachment.format {
-                Some(Format::Rgba8Unorm) => {
+                Some(format) if format.is_color() => {
                     render_attachments.push(color_attachment);
                     color_attachment_index += 1;
                 }
-                Some(Format::Rgba8Srgb) => {
-                    render_attachments.push(color_attachment);
-                    color_attachment_index += 1;
+                Some(format) if format.is_depth() && format.is_stencil() => {
+                    render_attachments.push(gl::DEPTH_STENCIL_ATTACHMENT)
+                }
+                Some(format) if format.is_depth() => render_attachments.push(gl::DEPTH_ATTACHMENT),
+                Some(format) if format.is_stencil() => {
+                    render_attachments.push(gl::STENCIL_ATTACHMENT)
                 }
-                Some(Format::D32Sfloat) => render_attachments.push(gl::DEPTH_STENCIL_ATTACHMENT),
                 _ => unimplemented!(),
             }
         }
@@ -1017,6 +1276,18 @@ impl d::Device<B> for Device {
         &self,
         info: i::SamplerInfo,
     ) -> Result<n::FatSampler, d::AllocationError> {
+        if let i::Anisotropic::On(fac) = info.anisotropic {
+            if fac > 1
+                && !self
+                    .share
+                    .features
+                    .contains(hal::Features::SAMPLER_ANISOTROPY)
+            {
+                error!("Anisotropic filtering is not supported");
+                return Err(d::AllocationError::MissingFeature);
+            }
+        }
+
         if !self
             .share
             .legacy_features
@@ -1107,8 +1378,10 @@ impl d::Device<B> for Device {
         let cpu_can_write = memory.can_upload();
 
         if self.share.private_caps.buffer_storage {
-            //TODO: gl::DYNAMIC_STORAGE_BIT | gl::MAP_PERSISTENT_BIT
-            let flags = memory.map_flags();
+            //TODO: gl::MAP_PERSISTENT_BIT
+            // Dynamic storage is required for `update_buffer`, which
+            // writes into the existing storage with `BufferSubData`.
+            let flags = memory.map_flags() | gl::DYNAMIC_STORAGE_BIT;
             //TODO: use *Named calls to avoid binding
             gl.BindBuffer(target, buffer.raw);
             gl.BufferStorage(target, buffer.requirements.size as _, ptr::null(), flags);
@@ -1152,6 +1425,9 @@ impl d::Device<B> for Device {
         assert!(self.share.private_caps.buffer_role_change);
         let target = gl::PIXEL_PACK_BUFFER;
         let access = memory.map_flags();
+        if access == 0 {
+            return Err(mapping::Error::InvalidAccess);
+        }
 
         let offset = *range.start().unwrap_or(&0);
         let size = *range.end().unwrap_or(&memory.size) - offset;
@@ -1193,7 +1469,8 @@ impl d::Device<B> for Device {
         I::Item: Borrow<(&'a n::Memory, R)>,
         R: RangeArg<u64>,
     {
-        warn!("memory range invalidation not implemented!");
+        // Buffers are mapped without `MAP_FLUSH_EXPLICIT_BIT`,
+        // writes become visible to the device when unmapping.
         Ok(())
     }
 
@@ -1206,7 +1483,9 @@ impl d::Device<B> for Device {
         I::Item: Borrow<(&'a n::Memory, R)>,
         R: RangeArg<u64>,
     {
-        unimplemented!()
+        // Non-persistent mappings observe the device writes that completed
+        // before `map_memory`, there is nothing to invalidate.
+        Ok(())
     }
 
     unsafe fn create_buffer_view<R: RangeArg<u64>>(
@@ -1225,68 +1504,190 @@ impl d::Device<B> for Device {
         format: Format,
         _tiling: i::Tiling,
         usage: i::Usage,
-        _view_caps: i::ViewCapabilities,
+        view_caps: i::ViewCapabilities,
     ) -> Result<n::Image, i::CreationError> {
         let gl = &self.share.context;
 
-        let (int_format, iformat, itype) = match format {
-            Format::Rgba8Unorm => (gl::RGBA8, gl::RGBA, gl::UNSIGNED_BYTE),
-            Format::Rgba8Srgb => (gl::SRGB8_ALPHA8, gl::RGBA, gl::UNSIGNED_BYTE),
-            Format::D32Sfloat => (
-                gl::DEPTH32F_STENCIL8,
-                gl::DEPTH_STENCIL,
-                gl::FLOAT_32_UNSIGNED_INT_24_8_REV,
-            ),
-            _ => unimplemented!(),
+        let (int_format, iformat, itype) = match conv::image_format_to_gl(format) {
+            Some(f) if self.share.is_format_supported(format) => f,
+            _ => return Err(i::CreationError::Format(format)),
         };
 
+        if num_levels == 0 || num_levels > kind.num_levels() {
+            return Err(i::CreationError::Levels(num_levels));
+        }
+
+        let cube = view_caps.contains(i::ViewCapabilities::KIND_CUBE);
+        if cube {
+            match kind {
+                i::Kind::D2(w, h, 6, 1) if w == h => {}
+                _ => return Err(i::CreationError::Kind),
+            }
+        }
+
+        let compressed = format.surface_desc().is_compressed();
         let channel = format.base_format().1;
 
+        // Compressed images can only be sampled and copied.
+        let render_usage =
+            i::Usage::COLOR_ATTACHMENT | i::Usage::DEPTH_STENCIL_ATTACHMENT | i::Usage::STORAGE;
+        if compressed && usage.intersects(render_usage) {
+            return Err(i::CreationError::Usage(usage));
+        }
+
+        let samples = kind.num_samples();
+        if samples > 1 {
+            // Multisampled images are plain 2D render targets.
+            match kind {
+                i::Kind::D2(_, _, 1, _) if !cube && !compressed => {}
+                _ => return Err(i::CreationError::Kind),
+            }
+            if !samples.is_power_of_two()
+                || self.share.limits.framebuffer_color_samples_count & samples == 0
+            {
+                return Err(i::CreationError::Samples(samples));
+            }
+        }
+
         let image = if num_levels > 1
+            || kind.num_layers() > 1
+            || compressed
             || usage.contains(i::Usage::STORAGE)
             || usage.contains(i::Usage::SAMPLED)
         {
+            let caps = &self.share.private_caps;
+            if samples > 1 && !caps.texture_storage_multisample && !caps.texture_multisample {
+                return Err(i::CreationError::Samples(samples));
+            }
+
             let mut name = 0;
             gl.GenTextures(1, &mut name);
-            match kind {
-                i::Kind::D2(w, h, 1, 1) => {
-                    gl.BindTexture(gl::TEXTURE_2D, name);
+            let target = match kind {
+                i::Kind::D2(w, h, 1, samples) if samples > 1 => {
+                    gl.BindTexture(gl::TEXTURE_2D_MULTISAMPLE, name);
+                    if self.share.private_caps.texture_storage_multisample {
+                        gl.TexStorage2DMultisample(
+                            gl::TEXTURE_2D_MULTISAMPLE,
+                            samples as _,
+                            int_format,
+                            w as _,
+                            h as _,
+                            gl::TRUE,
+                        );
+                    } else {
+                        gl.TexImage2DMultisample(
+                            gl::TEXTURE_2D_MULTISAMPLE,
+                            samples as _,
+                            int_format,
+                            w as _,
+                            h as _,
+                            gl::TRUE,
+                        );
+                    }
+                    gl::TEXTURE_2D_MULTISAMPLE
+                }
+                i::Kind::D2(w, h, layers, 1) if layers == 1 || cube => {
+                    let target = if cube {
+                        gl::TEXTURE_CUBE_MAP
+                    } else {
+                        gl::TEXTURE_2D
+                    };
+                    gl.BindTexture(target, name);
                     if self.share.private_caps.image_storage {
-                        gl.TexStorage2D(
-                            gl::TEXTURE_2D,
+                        gl.TexStorage2D(target, num_levels as _, int_format, w as _, h as _);
+                    } else {
+                        gl.TexParameteri(target, gl::TEXTURE_MAX_LEVEL, (num_levels - 1) as _);
+                        let mut w = w;
+                        let mut h = h;
+                        for i in 0..num_levels {
+                            for layer in 0..layers {
+                                let layer_target = conv::texture_layer_target(target, layer);
+                                if compressed {
+                                    gl.CompressedTexImage2D(
+                                        layer_target,
+                                        i as _,
+                                        int_format,
+                                        w as _,
+                                        h as _,
+                                        0,
+                                        conv::image_region_size(format, w, h, 1) as _,
+                                        std::ptr::null(),
+                                    );
+                                } else {
+                                    gl.TexImage2D(
+                                        layer_target,
+                                        i as _,
+                                        int_format as _,
+                                        w as _,
+                                        h as _,
+                                        0,
+                                        iformat,
+                                        itype,
+                                        std::ptr::null(),
+                                    );
+                                }
+                            }
+                            w = std::cmp::max(w / 2, 1);
+                            h = std::cmp::max(h / 2, 1);
+                        }
+                    }
+                    target
+                }
+                i::Kind::D2(w, h, layers, 1) => {
+                    gl.BindTexture(gl::TEXTURE_2D_ARRAY, name);
+                    if self.share.private_caps.image_storage {
+                        gl.TexStorage3D(
+                            gl::TEXTURE_2D_ARRAY,
                             num_levels as _,
                             int_format,
                             w as _,
                             h as _,
+                            layers as _,
                         );
                     } else {
                         gl.TexParameteri(
-                            gl::TEXTURE_2D,
+                            gl::TEXTURE_2D_ARRAY,
                             gl::TEXTURE_MAX_LEVEL,
                             (num_levels - 1) as _,
                         );
                         let mut w = w;
                         let mut h = h;
                         for i in 0..num_levels {
-                            gl.TexImage2D(
-                                gl::TEXTURE_2D,
-                                i as _,
-                                int_format as _,
-                                w as _,
-                                h as _,
-                                0,
-                                iformat,
-                                itype,
-                                std::ptr::null(),
-                            );
+                            if compressed {
+                                gl.CompressedTexImage3D(
+                                    gl::TEXTURE_2D_ARRAY,
+                                    i as _,
+                                    int_format,
+                                    w as _,
+                                    h as _,
+                                    layers as _,
+                                    0,
+                                    conv::image_region_size(format, w, h, layers as _) as _,
+                                    std::ptr::null(),
+                                );
+                            } else {
+                                gl.TexImage3D(
+                                    gl::TEXTURE_2D_ARRAY,
+                                    i as _,
+                                    int_format as _,
+                                    w as _,
+                                    h as _,
+                                    layers as _,
+                                    0,
+                                    iformat,
+                                    itype,
+                                    std::ptr::null(),
+                                );
+                            }
                             w = std::cmp::max(w / 2, 1);
                             h = std::cmp::max(h / 2, 1);
                         }
                     }
+                    gl::TEXTURE_2D_ARRAY
                 }
                 _ => unimplemented!(),
             };
-            n::ImageKind::Texture(name)
+            n::ImageKind::Texture(name, target)
         } else {
             let mut name = 0;
             gl.GenRenderbuffers(1, &mut name);
@@ -1295,15 +1696,28 @@ impl d::Device<B> for Device {
                     gl.BindRenderbuffer(gl::RENDERBUFFER, name);
                     gl.RenderbufferStorage(gl::RENDERBUFFER, int_format, w as _, h as _);
                 }
+                i::Kind::D2(w, h, 1, samples) => {
+                    gl.BindRenderbuffer(gl::RENDERBUFFER, name);
+                    gl.RenderbufferStorageMultisample(
+                        gl::RENDERBUFFER,
+                        samples as _,
+                        int_format,
+                        w as _,
+                        h as _,
+                    );
+                }
                 _ => unimplemented!(),
             };
             n::ImageKind::Surface(name)
         };
 
-        let surface_desc = format.base_format().0.desc();
-        let bytes_per_texel = surface_desc.bits / 8;
-        let ext = kind.extent();
-        let size = (ext.width * ext.height * ext.depth) as u64 * bytes_per_texel as u64;
+        let size = (0..num_levels)
+            .map(|level| {
+                let ext = kind.level_extent(level);
+                conv::image_region_size(format, ext.width, ext.height, ext.depth)
+                    * kind.num_layers() as u64
+            })
+            .sum();
 
         if let Err(err) = self.share.check() {
             panic!(
@@ -1314,6 +1728,10 @@ impl d::Device<B> for Device {
 
         Ok(n::Image {
             kind: image,
+            format,
+            extent: kind.extent(),
+            num_levels,
+            num_layers: kind.num_layers(),
             channel,
             requirements: memory::Requirements {
                 size,
@@ -1347,22 +1765,30 @@ impl d::Device<B> for Device {
     unsafe fn create_image_view(
         &self,
         image: &n::Image,
-        _kind: i::ViewKind,
+        kind: i::ViewKind,
         _format: Format,
         swizzle: Swizzle,
         range: i::SubresourceRange,
     ) -> Result<n::ImageView, i::ViewError> {
-        //TODO: check if `layers.end` covers all the layers
+        if range.levels.start >= range.levels.end || range.levels.end > image.num_levels {
+            return Err(i::ViewError::Level(range.levels.start));
+        }
+        if range.layers.start >= range.layers.end || range.layers.end > image.num_layers {
+            return Err(i::ViewError::Layer(i::LayerError::OutOfBounds(
+                range.layers,
+            )));
+        }
+        // Framebuffer attachments use the first level of the range, sampling
+        // is restricted to the range when the view is bound.
         let level = range.levels.start;
-        assert_eq!(level + 1, range.levels.end);
         //assert_eq!(format, image.format);
         assert_eq!(swizzle, Swizzle::NO);
         //TODO: check format
         match image.kind {
             n::ImageKind::Surface(surface) => {
-                if range.levels.start == 0 && range.layers.start == 0 {
+                if range.levels == (0..1) && range.layers.start == 0 {
                     Ok(n::ImageView::Surface(surface))
-                } else if level != 0 {
+                } else if range.levels != (0..1) {
                     Err(i::ViewError::Level(level)) //TODO
                 } else {
                     Err(i::ViewError::Layer(i::LayerError::OutOfBounds(
@@ -1370,13 +1796,53 @@ impl d::Device<B> for Device {
                     )))
                 }
             }
-            n::ImageKind::Texture(texture) => {
-                //TODO: check that `level` exists
-                if range.layers.start == 0 {
-                    Ok(n::ImageView::Texture(texture, level))
+            n::ImageKind::Texture(texture, target)
+                if self.share.private_caps.texture_view
+                    && self.share.private_caps.image_storage
+                    && (range.levels != (0..image.num_levels)
+                        || range.layers != (0..image.num_layers)) =>
+            {
+                let (int_format, _, _) = conv::image_format_to_gl(image.format).unwrap();
+                let multisampled = target == gl::TEXTURE_2D_MULTISAMPLE
+                    || target == gl::TEXTURE_2D_MULTISAMPLE_ARRAY;
+                let view_target = conv::view_kind_to_gl(kind, multisampled);
+                let gl = &self.share.context;
+                let mut name = 0;
+                gl.GenTextures(1, &mut name);
+                gl.TextureView(
+                    name,
+                    view_target,
+                    texture,
+                    int_format,
+                    range.levels.start as _,
+                    (range.levels.end - range.levels.start) as _,
+                    range.layers.start as _,
+                    (range.layers.end - range.layers.start) as _,
+                );
+                if let Err(err) = self.share.check() {
+                    gl.DeleteTextures(1, &name);
+                    error!("Error creating a view of {:?}: {:?}", range, err);
+                    return Err(i::ViewError::BadKind(kind));
+                }
+                Ok(n::ImageView::View(name, view_target))
+            }
+            n::ImageKind::Texture(texture, target) => {
+                // A single layer of an array texture is only accessible
+                // through a non-array view, e.g. for framebuffer attachments.
+                let layered = target == gl::TEXTURE_2D
+                    || target == gl::TEXTURE_2D_MULTISAMPLE
+                    || kind != i::ViewKind::D2;
+                if range.layers.start == 0 && layered {
+                    Ok(n::ImageView::Texture(
+                        texture,
+                        target,
+                        level,
+                        range.levels.end,
+                    ))
                 } else if range.layers.start + 1 == range.layers.end {
                     Ok(n::ImageView::TextureLayer(
                         texture,
+                        target,
                         level,
                         range.layers.start,
                     ))
@@ -1427,6 +1893,15 @@ impl d::Device<B> for Device {
             let set = &mut write.set;
             let mut bindings = set.bindings.lock().unwrap();
             let binding = write.binding;
+            let ty = set
+                .layout
+                .iter()
+                .find(|b| b.binding == binding)
+                .map(|b| b.ty);
+            let buffer_ty = match ty {
+                Some(pso::DescriptorType::StorageBuffer) => n::BindingTypes::StorageBuffers,
+                _ => n::BindingTypes::UniformBuffers,
+            };
             let mut offset = write.array_offset as _;
 
             for descriptor in write.descriptors {
@@ -1437,7 +1912,7 @@ impl d::Device<B> for Device {
                         let size = (end - start) as _;
 
                         bindings.push(n::DescSetBindings::Buffer {
-                            ty: n::BindingTypes::UniformBuffers,
+                            ty: buffer_ty,
                             binding,
                             buffer: buffer.raw,
                             offset,
@@ -1448,10 +1923,26 @@ impl d::Device<B> for Device {
                     }
                     pso::Descriptor::CombinedImageSampler(view, _layout, sampler) => {
                         match view {
-                            n::ImageView::Texture(tex, _)
-                            | n::ImageView::TextureLayer(tex, _, _) => {
-                                bindings.push(n::DescSetBindings::Texture(binding, *tex))
+                            n::ImageView::Texture(tex, target, start, end) => {
+                                bindings.push(n::DescSetBindings::Texture(
+                                    binding,
+                                    *tex,
+                                    *target,
+                                    self.sampled_levels(*target, *start, *end),
+                                ))
+                            }
+                            // Base and maximum levels are texture state, so views of a
+                            // single level have to set them as well.
+                            n::ImageView::TextureLayer(tex, target, level, _) => {
+                                bindings.push(n::DescSetBindings::Texture(
+                                    binding,
+                                    *tex,
+                                    *target,
+                                    self.sampled_levels(*target, *level, *level + 1),
+                                ))
                             }
+                            n::ImageView::View(tex, target) => bindings
+                                .push(n::DescSetBindings::Texture(binding, *tex, *target, None)),
                             n::ImageView::Surface(_) => unimplemented!(),
                         }
                         match sampler {
@@ -1462,9 +1953,48 @@ impl d::Device<B> for Device {
                                 .push(n::DescSetBindings::SamplerInfo(binding, info.clone())),
                         }
                     }
+                    pso::Descriptor::Image(view, _layout)
+                        if ty == Some(pso::DescriptorType::StorageImage) =>
+                    {
+                        let (texture, target, level, layer) = match **view {
+                            n::ImageView::Texture(tex, target, level, _) => {
+                                (tex, target, level, None)
+                            }
+                            n::ImageView::TextureLayer(tex, target, level, layer) => {
+                                (tex, target, level, Some(layer))
+                            }
+                            n::ImageView::View(tex, target) => (tex, target, 0, None),
+                            n::ImageView::Surface(_) => panic!(
+                                "Texture was created with only render target usage which is invalid."
+                            ),
+                        };
+                        bindings.push(n::DescSetBindings::StorageImage {
+                            binding,
+                            texture,
+                            level,
+                            layer,
+                            format: self.texture_internal_format(texture, target, level),
+                        });
+                    }
                     pso::Descriptor::Image(view, _layout) => match view {
-                        n::ImageView::Texture(tex, _) | n::ImageView::TextureLayer(tex, _, _) => {
-                            bindings.push(n::DescSetBindings::Texture(binding, *tex))
+                        n::ImageView::Texture(tex, target, start, end) => {
+                            bindings.push(n::DescSetBindings::Texture(
+                                binding,
+                                *tex,
+                                *target,
+                                self.sampled_levels(*target, *start, *end),
+                            ))
+                        }
+                        n::ImageView::TextureLayer(tex, target, level, _) => {
+                            bindings.push(n::DescSetBindings::Texture(
+                                binding,
+                                *tex,
+                                *target,
+                                self.sampled_levels(*target, *level, *level + 1),
+                            ))
+                        }
+                        n::ImageView::View(tex, target) => {
+                            bindings.push(n::DescSetBindings::Texture(binding, *tex, *target, None))
                         }
                         n::ImageView::Surface(_) => panic!(
                             "Texture was created with only render target usage which is invalid."
@@ -1548,8 +2078,19 @@ impl d::Device<B> for Device {
         }
     }
 
-    unsafe fn get_fence_status(&self, _: &n::Fence) -> Result<bool, d::DeviceLost> {
-        unimplemented!()
+    unsafe fn get_fence_status(&self, fence: &n::Fence) -> Result<bool, d::DeviceLost> {
+        if !self.share.private_caps.sync {
+            return Ok(true);
+        }
+        let sync = fence.0.get();
+        if sync.is_null() {
+            // Not signalled on creation and not submitted yet.
+            return Ok(false);
+        }
+        let gl = &self.share.context;
+        let mut status = 0;
+        gl.GetSynciv(sync, gl::SYNC_STATUS, 1, ptr::null_mut(), &mut status);
+        Ok(status == gl::SIGNALED as _)
     }
 
     unsafe fn free_memory(&self, _memory: n::Memory) {
@@ -1558,25 +2099,78 @@ impl d::Device<B> for Device {
 
     unsafe fn create_query_pool(
         &self,
-        _ty: query::Type,
-        _count: query::Id,
-    ) -> Result<(), query::CreationError> {
-        unimplemented!()
+        ty: query::Type,
+        count: query::Id,
+    ) -> Result<n::QueryPool, query::CreationError> {
+        let supported = match ty {
+            query::Type::Occlusion => self.share.private_caps.occlusion_query,
+            query::Type::Timestamp => self.share.private_caps.timer_query,
+            _ => false,
+        };
+        if !supported {
+            return Err(query::CreationError::Unsupported(ty));
+        }
+
+        let gl = &self.share.context;
+        let mut queries = vec![0; count as usize];
+        gl.GenQueries(count as _, queries.as_mut_ptr());
+
+        if let Err(err) = self.share.check() {
+            error!("Error creating query pool: {:?}", err);
+            return Err(d::OutOfMemory::OutOfDeviceMemory.into());
+        }
+
+        Ok(n::QueryPool { ty, queries })
     }
 
-    unsafe fn destroy_query_pool(&self, _: ()) {
-        unimplemented!()
+    unsafe fn destroy_query_pool(&self, pool: n::QueryPool) {
+        let gl = &self.share.context;
+        gl.DeleteQueries(pool.queries.len() as _, pool.queries.as_ptr());
     }
 
     unsafe fn get_query_pool_results(
         &self,
-        _pool: &(),
-        _queries: Range<query::Id>,
-        _data: &mut [u8],
-        _stride: buffer::Offset,
-        _flags: query::ResultFlags,
+        pool: &n::QueryPool,
+        queries: Range<query::Id>,
+        data: &mut [u8],
+        stride: buffer::Offset,
+        flags: query::ResultFlags,
     ) -> Result<bool, d::OomOrDeviceLost> {
-        unimplemented!()
+        let gl = &self.share.context;
+        let value_size = if flags.contains(query::ResultFlags::BITS_64) {
+            8
+        } else {
+            4
+        };
+        let write_value = |data: &mut [u8], offset: usize, value: u64| {
+            if value_size == 8 {
+                data[offset..offset + 8].copy_from_slice(&value.to_ne_bytes());
+            } else {
+                data[offset..offset + 4].copy_from_slice(&(value as u32).to_ne_bytes());
+            }
+        };
+
+        let mut all_available = true;
+        for (i, id) in queries.enumerate() {
+            let query = pool.queries[id as usize];
+            let offset = i * stride as usize;
+
+            let result = query_result(gl, query, flags);
+            all_available &= result.is_some();
+
+            match result {
+                Some(value) => write_value(data, offset, value),
+                // GL can't report intermediate results.
+                None if flags.contains(query::ResultFlags::PARTIAL) => write_value(data, offset, 0),
+                None => (),
+            }
+
+            if flags.contains(query::ResultFlags::WITH_AVAILABILITY) {
+                write_value(data, offset + value_size, result.is_some() as u64);
+            }
+        }
+
+        Ok(all_available || flags.contains(query::ResultFlags::PARTIAL))
     }
 
     unsafe fn destroy_shader_module(&self, _: n::ShaderModule) {
@@ -1615,12 +2209,15 @@ impl d::Device<B> for Device {
         let gl = &self.share.context;
         match image.kind {
             n::ImageKind::Surface(rb) => gl.DeleteRenderbuffers(1, &rb),
-            n::ImageKind::Texture(t) => gl.DeleteTextures(1, &t),
+            n::ImageKind::Texture(t, _) => gl.DeleteTextures(1, &t),
         }
     }
 
-    unsafe fn destroy_image_view(&self, _image_view: n::ImageView) {
-        // Nothing to do
+    unsafe fn destroy_image_view(&self, image_view: n::ImageView) {
+        // Only texture views own a GL object.
+        if let n::ImageView::View(texture, _) = image_view {
+            self.share.context.DeleteTextures(1, &texture);
+        }
     }
 
     unsafe fn destroy_sampler(&self, sampler: n::FatSampler) {
@@ -1660,8 +2257,14 @@ impl d::Device<B> for Device {
         Ok(self.create_swapchain_impl(surface, config))
     }
 
-    unsafe fn destroy_swapchain(&self, _swapchain: Swapchain) {
-        // Nothing to do
+    unsafe fn destroy_swapchain(&self, swapchain: Swapchain) {
+        let gl = &self.share.context;
+        for image in swapchain.images {
+            match image {
+                n::ImageKind::Surface(rb) => gl.DeleteRenderbuffers(1, &rb),
+                n::ImageKind::Texture(t, _) => gl.DeleteTextures(1, &t),
+            }
+        }
     }
 
     fn wait_idle(&self) -> Result<(), error::HostExecutionError> {
@@ -1670,6 +2273,81 @@ impl d::Device<B> for Device {
         }
         Ok(())
     }
+
+    unsafe fn set_image_name(&self, image: &mut n::Image, name: &str) {
+        match image.kind {
+            n::ImageKind::Surface(surface) => {
+                self.set_object_label(gl::RENDERBUFFER, surface, name)
+            }
+            n::ImageKind::Texture(texture, _) => self.set_object_label(gl::TEXTURE, texture, name),
+        }
+    }
+
+    unsafe fn set_buffer_name(&self, buffer: &mut n::Buffer, name: &str) {
+        self.set_object_label(gl::BUFFER, buffer.raw, name)
+    }
+
+    unsafe fn set_command_buffer_name(
+        &self,
+        _command_buffer: &mut crate::command::RawCommandBuffer,
+        _name: &str,
+    ) {
+        // Command buffers are recorded on the CPU, there is no GL object to label.
+    }
+
+    unsafe fn set_semaphore_name(&self, _semaphore: &mut n::Semaphore, _name: &str) {
+        // Semaphores are no-ops on GL.
+    }
+
+    unsafe fn set_fence_name(&self, _fence: &mut n::Fence, _name: &str) {
+        // The underlying sync object is replaced on every submission.
+    }
+
+    unsafe fn set_framebuffer_name(&self, framebuffer: &mut n::FrameBuffer, name: &str) {
+        self.set_object_label(gl::FRAMEBUFFER, *framebuffer, name)
+    }
+
+    unsafe fn set_render_pass_name(&self, _render_pass: &mut n::RenderPass, _name: &str) {
+        // Render passes are emulated, there is no GL object to label.
+    }
+
+    unsafe fn set_descriptor_set_name(&self, _descriptor_set: &mut n::DescriptorSet, _name: &str) {
+        // Descriptor sets are emulated, there is no GL object to label.
+    }
+
+    unsafe fn set_descriptor_set_layout_name(
+        &self,
+        _descriptor_set_layout: &mut n::DescriptorSetLayout,
+        _name: &str,
+    ) {
+        // Descriptor set layouts are emulated, there is no GL object to label.
+    }
+}
+
+/// Read the result of a query on the host, or `None` if it isn't available
+/// yet and `flags` don't ask to wait for it.
+pub(crate) unsafe fn query_result(
+    gl: &GlContainer,
+    query: n::Query,
+    flags: query::ResultFlags,
+) -> Option<u64> {
+    if !flags.contains(query::ResultFlags::WAIT) {
+        let mut available = 0;
+        gl.GetQueryObjectuiv(query, gl::QUERY_RESULT_AVAILABLE, &mut available);
+        if available == gl::FALSE as _ {
+            return None;
+        }
+    }
+
+    if flags.contains(query::ResultFlags::BITS_64) && gl.GetQueryObjectui64v.is_loaded() {
+        let mut value = 0;
+        gl.GetQueryObjectui64v(query, gl::QUERY_RESULT, &mut value);
+        Some(value)
+    } else {
+        let mut value = 0;
+        gl.GetQueryObjectuiv(query, gl::QUERY_RESULT, &mut value);
+        Some(value as u64)
+    }
 }
 
 pub(crate) fn wait_fence(fence: &n::Fence, share: &Starc<Share>, timeout_ns: u64) -> GLenum {
@@ -1688,3 +2366,26 @@ pub(crate) fn wait_fence(fence: &n::Fence, share: &Starc<Share>, timeout_ns: u64
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{clamp_anisotropy, numbered_lines};
+
+    #[test]
+    fn test_clamp_anisotropy() {
+        assert_eq!(clamp_anisotropy(4, 16.0), 4.0);
+        assert_eq!(clamp_anisotropy(16, 16.0), 16.0);
+        assert_eq!(clamp_anisotropy(16, 8.0), 8.0);
+        assert_eq!(clamp_anisotropy(2, 1.0), 1.0);
+        assert_eq!(clamp_anisotropy(0, 16.0), 1.0);
+    }
+
+    #[test]
+    fn test_numbered_lines() {
+        assert_eq!(numbered_lines(""), "");
+        assert_eq!(
+            numbered_lines("#version 450\nvoid main() {}"),
+            "   1: #version 450\n   2: void main() {}\n"
+        );
+    }
+}
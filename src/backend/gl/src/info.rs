@@ -1,4 +1,4 @@
-use crate::hal::{Features, Limits};
+use crate::hal::{image, Features, Limits};
 use crate::{gl, Error, GlContainer};
 use std::collections::HashSet;
 use std::{ffi, fmt, mem, str};
@@ -178,6 +178,38 @@ pub struct PrivateCaps {
     pub map: bool,
     /// Indicates if we only have support via the EXT.
     pub sampler_anisotropy_ext: bool,
+    /// Occlusion queries
+    pub occlusion_query: bool,
+    /// Occlusion queries reporting only if any samples passed
+    pub any_samples_passed: bool,
+    /// Timestamp queries
+    pub timer_query: bool,
+    /// Writing query results directly into buffers
+    pub query_buffer: bool,
+    /// Primitive restart using the maximum value of the index type
+    pub primitive_restart_fixed_index: bool,
+    /// Primitive restart using a user specified index
+    pub primitive_restart: bool,
+    /// Debug groups and markers
+    pub debug_markers: bool,
+    /// Debug labels on objects
+    pub object_labels: bool,
+    /// BC1, BC2 and BC3 compressed textures
+    pub texture_compression_s3tc: bool,
+    /// BC4 and BC5 compressed textures
+    pub texture_compression_rgtc: bool,
+    /// BC6H and BC7 compressed textures
+    pub texture_compression_bptc: bool,
+    /// ETC2 and EAC compressed textures
+    pub texture_compression_etc2: bool,
+    /// Coverage sample masks
+    pub sample_mask: bool,
+    /// Views over sub-ranges of immutable textures
+    pub texture_view: bool,
+    /// Mutable multisampled textures
+    pub texture_multisample: bool,
+    /// Immutable multisampled textures
+    pub texture_storage_multisample: bool,
 }
 
 /// OpenGL implementation information
@@ -306,7 +338,10 @@ pub(crate) fn query_all(gl: &GlContainer) -> (Info, Features, LegacyFeatures, Li
     use self::Requirement::*;
     let info = Info::get(gl);
     let max_texture_size = get_usize(gl, gl::MAX_TEXTURE_SIZE).unwrap_or(64) as u32;
-    let max_color_attachments = get_usize(gl, gl::MAX_COLOR_ATTACHMENTS).unwrap_or(8) as u8;
+    let max_color_attachments = get_usize(gl, gl::MAX_COLOR_ATTACHMENTS).unwrap_or(8);
+    // All power-of-two sample counts up to the maximum are supported.
+    let max_samples = get_usize(gl, gl::MAX_SAMPLES).unwrap_or(1).max(1);
+    let sample_count_mask = ((max_samples << 1) - 1) as image::NumSamples;
 
     let mut limits = Limits {
         max_image_1d_size: max_texture_size,
@@ -320,26 +355,40 @@ pub(crate) fn query_all(gl: &GlContainer) -> (Info, Features, LegacyFeatures, Li
         optimal_buffer_copy_pitch_alignment: 1,
         min_texel_buffer_offset_alignment: 1,   // TODO
         min_uniform_buffer_offset_alignment: 1, // TODO
-        min_storage_buffer_offset_alignment: 1, // TODO
-        framebuffer_color_samples_count: max_color_attachments,
+        framebuffer_color_samples_count: sample_count_mask,
+        framebuffer_depth_samples_count: sample_count_mask,
+        framebuffer_stencil_samples_count: sample_count_mask,
+        max_color_attachments,
         ..Limits::default()
     };
 
     if info.is_supported(&[Core(4, 0), Ext("GL_ARB_tessellation_shader")]) {
         limits.max_patch_size = get_usize(gl, gl::MAX_PATCH_VERTICES).unwrap_or(0) as _;
     }
-    if info.is_supported(&[Core(4, 1)]) {
-        // TODO: extension
-        limits.max_viewports = get_usize(gl, gl::MAX_VIEWPORTS).unwrap_or(0);
+    if info.is_supported(&[
+        Core(4, 3),
+        Es(3, 1),
+        Ext("GL_ARB_shader_storage_buffer_object"),
+    ]) {
+        limits.max_storage_buffer_range =
+            get_usize(gl, gl::MAX_SHADER_STORAGE_BLOCK_SIZE).unwrap_or(0) as _;
+        limits.max_per_stage_descriptor_storage_buffers =
+            get_usize(gl, gl::MAX_FRAGMENT_SHADER_STORAGE_BLOCKS).unwrap_or(0);
+        limits.max_descriptor_set_storage_buffers =
+            get_usize(gl, gl::MAX_SHADER_STORAGE_BUFFER_BINDINGS).unwrap_or(0);
+        limits.min_storage_buffer_offset_alignment =
+            get_usize(gl, gl::SHADER_STORAGE_BUFFER_OFFSET_ALIGNMENT).unwrap_or(1) as _;
+    }
+    if info.is_supported(&[Core(4, 2), Es(3, 1), Ext("GL_ARB_shader_image_load_store")]) {
+        limits.max_per_stage_descriptor_storage_images =
+            get_usize(gl, gl::MAX_FRAGMENT_IMAGE_UNIFORMS).unwrap_or(0);
+        limits.max_descriptor_set_storage_images = get_usize(gl, gl::MAX_IMAGE_UNITS).unwrap_or(0);
+    }
+    if info.is_supported(&[Core(4, 1), Ext("GL_ARB_viewport_array")]) {
+        limits.max_viewports = get_usize(gl, gl::MAX_VIEWPORTS).unwrap_or(1);
     }
 
-    if false
-        && info.is_supported(&[
-            //TODO: enable when compute is implemented
-            Core(4, 3),
-            Ext("GL_ARB_compute_shader"),
-        ])
-    {
+    if info.is_supported(&[Core(4, 3), Es(3, 1), Ext("GL_ARB_compute_shader")]) {
         let mut values = [0 as gl::types::GLint; 2];
         for (i, (count, size)) in limits
             .max_compute_work_group_count
@@ -365,6 +414,44 @@ pub(crate) fn query_all(gl: &GlContainer) -> (Info, Features, LegacyFeatures, Li
         Ext("GL_EXT_texture_filter_anisotropic"),
     ]) {
         features |= Features::SAMPLER_ANISOTROPY;
+        let mut max_anisotropy = 1.0;
+        unsafe { gl.GetFloatv(gl::MAX_TEXTURE_MAX_ANISOTROPY_EXT, &mut max_anisotropy) };
+        limits.max_sampler_anisotropy = max_anisotropy;
+    }
+    if info.is_supported(&[
+        Core(3, 2),
+        Es(3, 2),
+        Ext("GL_ARB_geometry_shader4"),
+        Ext("GL_EXT_geometry_shader"),
+    ]) {
+        features |= Features::GEOMETRY_SHADER;
+    }
+    if info.is_supported(&[
+        Core(3, 3),
+        Ext("GL_ARB_blend_func_extended"),
+        Ext("GL_EXT_blend_func_extended"),
+    ]) {
+        features |= Features::DUAL_SRC_BLENDING;
+    }
+    if info.is_supported(&[Core(4, 0), Es(3, 2), Ext("GL_ARB_draw_buffers_blend")]) {
+        features |= Features::INDEPENDENT_BLENDING;
+    }
+    if info.is_supported(&[Core(4, 0), Ext("GL_ARB_tessellation_shader")]) {
+        features |= Features::TESSELLATION_SHADER;
+    }
+    if info.is_supported(&[Core(4, 1), Ext("GL_ARB_viewport_array")]) {
+        features |= Features::MULTI_VIEWPORTS;
+    }
+    if !info.version.is_embedded {
+        features |= Features::NON_FILL_POLYGON_MODE;
+    }
+    if info.is_supported(&[Core(4, 0), Es(3, 2), Ext("GL_ARB_sample_shading")]) {
+        features |= Features::SAMPLE_RATE_SHADING;
+    }
+    let mut line_width_range = [1.0; 2];
+    unsafe { gl.GetFloatv(gl::ALIASED_LINE_WIDTH_RANGE, line_width_range.as_mut_ptr()) };
+    if line_width_range[1] > 1.0 {
+        features |= Features::LINE_WIDTH;
     }
     if info.is_supported(&[Core(4, 2)]) {
         legacy |= LegacyFeatures::EXPLICIT_LAYOUTS_IN_SHADER;
@@ -372,10 +459,13 @@ pub(crate) fn query_all(gl: &GlContainer) -> (Info, Features, LegacyFeatures, Li
     if info.is_supported(&[Core(3, 3), Es(3, 0), Ext("GL_ARB_instanced_arrays")]) {
         features |= Features::INSTANCE_RATE;
     }
-    if info.is_supported(&[Core(3, 3)]) {
-        // TODO: extension
+    // Not available on GLES at all, but core since GL 1.4 on desktop.
+    if info.is_supported(&[Core(1, 4)]) {
         features |= Features::SAMPLER_MIP_LOD_BIAS;
     }
+    if info.is_supported(&[Core(1, 5), Ext("GL_ARB_occlusion_query")]) {
+        features |= Features::PRECISE_OCCLUSION_QUERY;
+    }
 
     if info.is_supported(&[Core(4, 3), Es(3, 1)]) {
         // TODO: extension
@@ -387,16 +477,22 @@ pub(crate) fn query_all(gl: &GlContainer) -> (Info, Features, LegacyFeatures, Li
     if info.is_supported(&[Core(4, 2), Ext("GL_ARB_base_instance")]) {
         legacy |= LegacyFeatures::DRAW_INSTANCED_BASE;
     }
-    if info.is_supported(&[Core(3, 2)]) {
-        // TODO: extension
+    if info.is_supported(&[
+        Core(3, 2),
+        Es(3, 2),
+        Ext("GL_ARB_draw_elements_base_vertex"),
+    ]) {
         legacy |= LegacyFeatures::DRAW_INDEXED_BASE;
     }
     if info.is_supported(&[Core(3, 1), Es(3, 0)]) {
         // TODO: extension
         legacy |= LegacyFeatures::DRAW_INDEXED_INSTANCED;
     }
-    if info.is_supported(&[Core(3, 2)]) {
-        // TODO: extension
+    if info.is_supported(&[
+        Core(3, 2),
+        Es(3, 2),
+        Ext("GL_ARB_draw_elements_base_vertex"),
+    ]) {
         legacy |= LegacyFeatures::DRAW_INDEXED_INSTANCED_BASE_VERTEX;
     }
     if info.is_supported(&[Core(4, 2)]) {
@@ -410,7 +506,12 @@ pub(crate) fn query_all(gl: &GlContainer) -> (Info, Features, LegacyFeatures, Li
     ]) {
         legacy |= LegacyFeatures::VERTEX_BASE;
     }
-    if info.is_supported(&[Core(3, 2), Ext("GL_ARB_framebuffer_sRGB")]) {
+    // GLES encodes writes to sRGB framebuffers without a switch.
+    if info.is_supported(&[
+        Core(3, 0),
+        Ext("GL_ARB_framebuffer_sRGB"),
+        Ext("GL_EXT_framebuffer_sRGB"),
+    ]) {
         legacy |= LegacyFeatures::SRGB_COLOR;
     }
     if info.is_supported(&[Core(3, 1), Es(3, 0), Ext("GL_ARB_uniform_buffer_object")]) {
@@ -431,8 +532,12 @@ pub(crate) fn query_all(gl: &GlContainer) -> (Info, Features, LegacyFeatures, Li
     if info.is_supported(&[Core(3, 3), Es(3, 0), Ext("GL_ARB_sampler_objects")]) {
         legacy |= LegacyFeatures::SAMPLER_OBJECTS;
     }
-    if info.is_supported(&[Core(3, 3)]) {
-        // TODO: extension
+    if info.is_supported(&[
+        Core(1, 0),
+        Es(3, 2),
+        Ext("GL_EXT_texture_border_clamp"),
+        Ext("GL_OES_texture_border_clamp"),
+    ]) {
         legacy |= LegacyFeatures::SAMPLER_BORDER_COLOR;
     }
     if info.is_supported(&[Core(3, 3), Es(3, 0)]) {
@@ -456,8 +561,70 @@ pub(crate) fn query_all(gl: &GlContainer) -> (Info, Features, LegacyFeatures, Li
         sampler_anisotropy_ext: !info
             .is_supported(&[Core(4, 6), Ext("GL_ARB_texture_filter_anisotropic")])
             && info.is_supported(&[Ext("GL_EXT_texture_filter_anisotropic")]),
+        occlusion_query: info.is_supported(&[Core(1, 5), Es(3, 0), Ext("GL_ARB_occlusion_query")]),
+        any_samples_passed: info.is_supported(&[
+            Core(3, 3),
+            Es(3, 0),
+            Ext("GL_ARB_occlusion_query2"),
+        ]),
+        timer_query: info.is_supported(&[Core(3, 3), Ext("GL_ARB_timer_query")]),
+        query_buffer: info.is_supported(&[Core(4, 4), Ext("GL_ARB_query_buffer_object")]),
+        primitive_restart_fixed_index: info.is_supported(&[
+            Core(4, 3),
+            Es(3, 0),
+            Ext("GL_ARB_ES3_compatibility"),
+        ]),
+        primitive_restart: info.is_supported(&[Core(3, 1)]),
+        debug_markers: info.is_supported(&[Core(4, 3), Es(3, 2), Ext("GL_KHR_debug")])
+            && gl.PushDebugGroup.is_loaded(),
+        object_labels: info.is_supported(&[Core(4, 3), Es(3, 2), Ext("GL_KHR_debug")])
+            && gl.ObjectLabel.is_loaded(),
+        texture_compression_s3tc: info.is_supported(&[Ext("GL_EXT_texture_compression_s3tc")]),
+        texture_compression_rgtc: info.is_supported(&[
+            Core(3, 0),
+            Ext("GL_ARB_texture_compression_rgtc"),
+            Ext("GL_EXT_texture_compression_rgtc"),
+        ]),
+        texture_compression_bptc: info.is_supported(&[
+            Core(4, 2),
+            Ext("GL_ARB_texture_compression_bptc"),
+            Ext("GL_EXT_texture_compression_bptc"),
+        ]),
+        texture_compression_etc2: info.is_supported(&[
+            Core(4, 3),
+            Es(3, 0),
+            Ext("GL_ARB_ES3_compatibility"),
+        ]),
+        sample_mask: info.is_supported(&[Core(3, 2), Es(3, 1), Ext("GL_ARB_texture_multisample")]),
+        texture_view: info.is_supported(&[
+            Core(4, 3),
+            Ext("GL_ARB_texture_view"),
+            Ext("GL_OES_texture_view"),
+            Ext("GL_EXT_texture_view"),
+        ]) && gl.TextureView.is_loaded(),
+        texture_multisample: info.is_supported(&[Core(3, 2), Ext("GL_ARB_texture_multisample")])
+            && gl.TexImage2DMultisample.is_loaded(),
+        texture_storage_multisample: info.is_supported(&[
+            Core(4, 3),
+            Es(3, 1),
+            Ext("GL_ARB_texture_storage_multisample"),
+        ]) && gl.TexStorage2DMultisample.is_loaded(),
     };
 
+    if private.texture_compression_s3tc
+        && private.texture_compression_rgtc
+        && private.texture_compression_bptc
+    {
+        features |= Features::FORMAT_BC;
+    }
+    if private.texture_compression_etc2 {
+        features |= Features::FORMAT_ETC2;
+    }
+    if private.timer_query {
+        // GL timestamps are specified in nanoseconds.
+        limits.timestamp_period = 1.0;
+    }
+
     (info, features, legacy, limits, private)
 }
 
@@ -94,7 +94,7 @@ impl hal::Backend for Backend {
 
     type Fence = native::Fence;
     type Semaphore = native::Semaphore;
-    type QueryPool = ();
+    type QueryPool = native::QueryPool;
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
@@ -146,6 +146,32 @@ impl Share {
         }
         Ok(())
     }
+
+    /// Check if images of the given format can be created.
+    fn is_format_supported(&self, format: hal::format::Format) -> bool {
+        use crate::hal::format::SurfaceType;
+
+        if conv::image_format_to_gl(format).is_none() {
+            return false;
+        }
+        match format.base_format().0 {
+            SurfaceType::BC1_RGB | SurfaceType::BC1_RGBA | SurfaceType::BC2 | SurfaceType::BC3 => {
+                self.private_caps.texture_compression_s3tc
+            }
+            SurfaceType::BC4 | SurfaceType::BC5 => self.private_caps.texture_compression_rgtc,
+            SurfaceType::BC6 | SurfaceType::BC7 => self.private_caps.texture_compression_bptc,
+            SurfaceType::ETC2_R8_G8_B8
+            | SurfaceType::ETC2_R8_G8_B8_A1
+            | SurfaceType::ETC2_R8_G8_B8_A8
+            | SurfaceType::EAC_R11
+            | SurfaceType::EAC_R11_G11 => self.private_caps.texture_compression_etc2,
+            // GLES only accepts BGRA data through extensions.
+            SurfaceType::B8_G8_R8_A8 | SurfaceType::A2_R10_G10_B10 => {
+                !self.info.version.is_embedded
+            }
+            _ => true,
+        }
+    }
 }
 
 /// Single-threaded `Arc`.
@@ -406,19 +432,103 @@ impl hal::PhysicalDevice<Backend> for PhysicalDevice {
         })
     }
 
-    fn format_properties(&self, _: Option<hal::format::Format>) -> hal::format::Properties {
-        unimplemented!()
+    fn format_properties(&self, format: Option<hal::format::Format>) -> hal::format::Properties {
+        use crate::hal::format::{BufferFeature, ChannelType, ImageFeature, Properties};
+
+        let format = match format {
+            Some(format) if self.0.is_format_supported(format) => format,
+            _ => return Properties::default(),
+        };
+
+        let desc = format.surface_desc();
+        let mut features = ImageFeature::SAMPLED;
+        if !desc.is_compressed() {
+            features |= ImageFeature::BLIT_SRC | ImageFeature::BLIT_DST;
+            if desc.aspects.contains(hal::format::Aspects::COLOR) {
+                features |= ImageFeature::COLOR_ATTACHMENT;
+            } else {
+                features |= ImageFeature::DEPTH_STENCIL_ATTACHMENT;
+            }
+        }
+        match format.base_format().1 {
+            ChannelType::Uint | ChannelType::Sint => {}
+            _ => {
+                features |= ImageFeature::SAMPLED_LINEAR;
+                if features.contains(ImageFeature::COLOR_ATTACHMENT) {
+                    features |= ImageFeature::COLOR_ATTACHMENT_BLEND;
+                }
+            }
+        }
+
+        let buffer_features = if conv::format_to_gl_format(format).is_some() {
+            BufferFeature::VERTEX
+        } else {
+            BufferFeature::empty()
+        };
+
+        // GL doesn't expose the tiling of images.
+        Properties {
+            linear_tiling: features,
+            optimal_tiling: features,
+            buffer_features,
+        }
     }
 
     fn image_format_properties(
         &self,
-        _format: hal::format::Format,
-        _dimensions: u8,
+        format: hal::format::Format,
+        dimensions: u8,
         _tiling: image::Tiling,
         _usage: image::Usage,
         _view_caps: image::ViewCapabilities,
     ) -> Option<image::FormatProperties> {
-        None //TODO
+        if !self.0.is_format_supported(format) {
+            return None;
+        }
+
+        let limits = &self.0.limits;
+        let (max_extent, max_layers) = match dimensions {
+            1 => (
+                image::Extent {
+                    width: limits.max_image_1d_size,
+                    height: 1,
+                    depth: 1,
+                },
+                limits.max_image_array_layers,
+            ),
+            2 => (
+                image::Extent {
+                    width: limits.max_image_2d_size,
+                    height: limits.max_image_2d_size,
+                    depth: 1,
+                },
+                limits.max_image_array_layers,
+            ),
+            3 => (
+                image::Extent {
+                    width: limits.max_image_3d_size,
+                    height: limits.max_image_3d_size,
+                    depth: limits.max_image_3d_size,
+                },
+                1,
+            ),
+            _ => return None,
+        };
+        // Only 2D images can be multisampled, see `create_image`.
+        let sample_count_mask = if dimensions == 2 && !format.surface_desc().is_compressed() {
+            limits.framebuffer_color_samples_count
+        } else {
+            1
+        };
+
+        Some(image::FormatProperties {
+            max_extent,
+            max_levels: 32
+                - max_extent.width.max(max_extent.height).leading_zeros() as image::Level,
+            max_layers,
+            sample_count_mask,
+            max_resource_size: !0,
+        })
     }
 
     fn memory_properties(&self) -> hal::MemoryProperties {
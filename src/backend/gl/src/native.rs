@@ -3,7 +3,7 @@ use std::sync::{Arc, Mutex, RwLock};
 
 use crate::hal::backend::FastHashMap;
 use crate::hal::memory::{Properties, Requirements};
-use crate::hal::{format, image as i, pass, pso};
+use crate::hal::{format, image as i, pass, pso, query};
 
 use crate::gl;
 use crate::Backend;
@@ -14,7 +14,9 @@ pub type Program = gl::types::GLuint;
 pub type FrameBuffer = gl::types::GLuint;
 pub type Surface = gl::types::GLuint;
 pub type Texture = gl::types::GLuint;
+pub type TextureTarget = gl::types::GLenum;
 pub type Sampler = gl::types::GLuint;
+pub type Query = gl::types::GLuint;
 
 pub type DescriptorSetLayout = Vec<pso::DescriptorSetLayoutBinding>;
 
@@ -45,6 +47,8 @@ impl Fence {
 pub enum BindingTypes {
     Images,
     UniformBuffers,
+    StorageBuffers,
+    StorageImages,
 }
 
 #[derive(Clone, Debug)]
@@ -132,12 +136,16 @@ pub struct GraphicsPipeline {
     pub(crate) program: Program,
     pub(crate) primitive: gl::types::GLenum,
     pub(crate) patch_size: Option<gl::types::GLint>,
+    pub(crate) primitive_restart: pso::PrimitiveRestart,
     pub(crate) blend_targets: Vec<pso::ColorBlendDesc>,
     pub(crate) attributes: Vec<AttributeDesc>,
     pub(crate) vertex_buffers: Vec<Option<pso::VertexBufferDesc>>,
     pub(crate) uniforms: Vec<UniformDesc>,
     pub(crate) rasterizer: pso::Rasterizer,
     pub(crate) depth: pso::DepthTest,
+    pub(crate) stencil: pso::StencilTest,
+    pub(crate) multisampling: Option<pso::Multisampling>,
+    pub(crate) baked_states: pso::BakedStates,
 }
 
 #[derive(Clone, Debug)]
@@ -148,6 +156,10 @@ pub struct ComputePipeline {
 #[derive(Copy, Clone, Debug)]
 pub struct Image {
     pub(crate) kind: ImageKind,
+    pub(crate) format: format::Format,
+    pub(crate) extent: i::Extent,
+    pub(crate) num_levels: i::Level,
+    pub(crate) num_layers: i::Layer,
     // Required for clearing operations
     pub(crate) channel: format::ChannelType,
     pub(crate) requirements: Requirements,
@@ -156,7 +168,7 @@ pub struct Image {
 #[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
 pub enum ImageKind {
     Surface(Surface),
-    Texture(Texture),
+    Texture(Texture, TextureTarget),
 }
 
 #[derive(Clone, PartialEq, Eq, Hash, Debug)]
@@ -170,8 +182,13 @@ pub enum FatSampler {
 #[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
 pub enum ImageView {
     Surface(Surface),
-    Texture(Texture, i::Level),
-    TextureLayer(Texture, i::Level, i::Layer),
+    /// Texture viewed from a level up to, but not including, an end level.
+    /// Framebuffer attachments only use the first level.
+    Texture(Texture, TextureTarget, i::Level, i::Level),
+    TextureLayer(Texture, TextureTarget, i::Level, i::Layer),
+    /// Texture object created with `glTextureView` over a sub-range of
+    /// levels and layers, owned by the view.
+    View(Texture, TextureTarget),
 }
 
 #[derive(Clone, PartialEq, Eq, Hash, Debug)]
@@ -183,14 +200,28 @@ pub(crate) enum DescSetBindings {
         offset: gl::types::GLintptr,
         size: gl::types::GLsizeiptr,
     },
-    Texture(pso::DescriptorBinding, Texture),
+    /// Sampled texture, with the range of levels to restrict it to when
+    /// the driver doesn't support texture views.
+    Texture(
+        pso::DescriptorBinding,
+        Texture,
+        TextureTarget,
+        Option<(i::Level, i::Level)>,
+    ),
+    StorageImage {
+        binding: pso::DescriptorBinding,
+        texture: Texture,
+        level: i::Level,
+        layer: Option<i::Layer>,
+        format: gl::types::GLenum,
+    },
     Sampler(pso::DescriptorBinding, Sampler),
     SamplerInfo(pso::DescriptorBinding, i::SamplerInfo),
 }
 
 #[derive(Clone, Debug)]
 pub struct DescriptorSet {
-    layout: DescriptorSetLayout,
+    pub(crate) layout: DescriptorSetLayout,
     pub(crate) bindings: Arc<Mutex<Vec<DescSetBindings>>>,
 }
 
@@ -292,6 +323,12 @@ pub struct PipelineLayout {
 // No inter-queue synchronization required for GL.
 pub struct Semaphore;
 
+#[derive(Debug)]
+pub struct QueryPool {
+    pub(crate) ty: query::Type,
+    pub(crate) queries: Vec<Query>,
+}
+
 #[derive(Clone, Debug)]
 pub struct AttributeDesc {
     pub(crate) location: gl::types::GLuint,
@@ -311,7 +348,8 @@ pub struct UniformDesc {
 
 #[derive(Debug, Clone, Copy)]
 pub enum VertexAttribFunction {
-    Float,   // glVertexAttribPointer
-    Integer, // glVertexAttribIPointer
-    Double,  // glVertexAttribLPointer
+    Float,      // glVertexAttribPointer
+    Normalized, // glVertexAttribPointer, with normalized = GL_TRUE
+    Integer,    // glVertexAttribIPointer
+    Double,     // glVertexAttribLPointer
 }
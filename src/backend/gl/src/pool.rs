@@ -56,6 +56,7 @@ pub enum BufferMemory {
 #[derive(Debug)]
 pub struct RawCommandPool {
     pub(crate) fbo: Option<n::FrameBuffer>,
+    pub(crate) blit_fbo: Option<n::FrameBuffer>,
     pub(crate) limits: command::Limits,
     pub(crate) memory: Arc<Mutex<BufferMemory>>,
 }
@@ -83,7 +84,7 @@ impl pool::RawCommandPool<Backend> for RawCommandPool {
 
     fn allocate_one(&mut self, _level: hal::command::RawLevel) -> RawCommandBuffer {
         // TODO: Implement secondary buffers
-        RawCommandBuffer::new(self.fbo, self.limits, self.memory.clone())
+        RawCommandBuffer::new(self.fbo, self.blit_fbo, self.limits, self.memory.clone())
     }
 
     unsafe fn free<I>(&mut self, buffers: I)
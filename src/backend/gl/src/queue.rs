@@ -3,13 +3,13 @@ use std::borrow::Borrow;
 use std::{mem, ptr, slice};
 
 use crate::hal;
-use crate::hal::error;
+use crate::hal::{buffer, error};
 
 use crate::gl;
 use smallvec::SmallVec;
 
-use crate::info::LegacyFeatures;
-use crate::{command as com, device, native, state, window};
+use crate::info::{LegacyFeatures, PrivateCaps};
+use crate::{command as com, conv, device, native, state, window};
 use crate::{Backend, Share};
 
 pub type ArrayBuffer = gl::types::GLuint;
@@ -167,10 +167,14 @@ impl CommandQueue {
             &native::ImageView::Surface(surface) => unsafe {
                 gl.FramebufferRenderbuffer(point, attachment, gl::RENDERBUFFER, surface);
             },
-            &native::ImageView::Texture(texture, level) => unsafe {
+            &native::ImageView::Texture(texture, _, level, _) => unsafe {
                 gl.FramebufferTexture(point, attachment, texture, level as gl::types::GLint);
             },
-            &native::ImageView::TextureLayer(texture, level, layer) => unsafe {
+            &native::ImageView::TextureLayer(texture, gl::TEXTURE_CUBE_MAP, level, layer) => unsafe {
+                let face = conv::texture_layer_target(gl::TEXTURE_CUBE_MAP, layer);
+                gl.FramebufferTexture2D(point, attachment, face, texture, level as _);
+            },
+            &native::ImageView::TextureLayer(texture, _, level, layer) => unsafe {
                 gl.FramebufferTextureLayer(
                     point,
                     attachment,
@@ -179,14 +183,29 @@ impl CommandQueue {
                     layer as gl::types::GLint,
                 );
             },
+            &native::ImageView::View(texture, _) => unsafe {
+                gl.FramebufferTexture(point, attachment, texture, 0);
+            },
         }
     }
 
-    fn _unbind_target(&mut self, point: gl::types::GLenum, attachment: gl::types::GLenum) {
+    fn unbind_target(&mut self, point: gl::types::GLenum, attachment: gl::types::GLenum) {
         let gl = &self.share.context;
         unsafe { gl.FramebufferTexture(point, attachment, 0, 0) };
     }
 
+    // Pick the query target for an occlusion query with the given flags.
+    // Imprecise queries only need to report if any samples passed.
+    fn occlusion_query_target(&self, flags: hal::query::ControlFlags) -> gl::types::GLenum {
+        if flags.contains(hal::query::ControlFlags::PRECISE)
+            || !self.share.private_caps.any_samples_passed
+        {
+            gl::SAMPLES_PASSED
+        } else {
+            gl::ANY_SAMPLES_PASSED
+        }
+    }
+
     /// Return a reference to a stored data object.
     fn get<T>(data: &[u8], ptr: com::BufferSlice) -> &[T] {
         let u32_size = mem::size_of::<T>();
@@ -201,6 +220,15 @@ impl CommandQueue {
         &data[ptr.offset as usize..(ptr.offset + ptr.size) as usize]
     }
 
+    fn disable_primitive_restart(gl: &gl::Gl, caps: &PrivateCaps) {
+        if caps.primitive_restart_fixed_index {
+            unsafe { gl.Disable(gl::PRIMITIVE_RESTART_FIXED_INDEX) };
+        }
+        if caps.primitive_restart {
+            unsafe { gl.Disable(gl::PRIMITIVE_RESTART) };
+        }
+    }
+
     // Reset the state to match our _expected_ state before executing
     // a command buffer.
     fn reset_state(&mut self) {
@@ -253,7 +281,10 @@ impl CommandQueue {
             };
         }
 
+        Self::disable_primitive_restart(gl, &self.share.private_caps);
+
         // Reset scissors
+        unsafe { gl.Disable(gl::SCISSOR_TEST) };
         if self.state.num_scissors == 1 {
             unsafe { gl.Scissor(0, 0, 0, 0) };
         } else if self.state.num_scissors > 1 {
@@ -391,6 +422,74 @@ impl CommandQueue {
                     error!("Instanced indexed drawing is not supported");
                 }
             }
+            com::Command::DrawIndirect {
+                primitive,
+                buffer,
+                offset,
+                draw_count,
+                stride,
+            } => {
+                let gl = &self.share.context;
+                if self
+                    .share
+                    .legacy_features
+                    .contains(LegacyFeatures::INDIRECT_EXECUTION)
+                {
+                    unsafe {
+                        gl.BindBuffer(gl::DRAW_INDIRECT_BUFFER, buffer);
+                        if draw_count > 1 && gl.MultiDrawArraysIndirect.is_loaded() {
+                            gl.MultiDrawArraysIndirect(
+                                primitive,
+                                offset as *const _,
+                                draw_count as _,
+                                stride as _,
+                            );
+                        } else {
+                            for i in 0..draw_count as buffer::Offset {
+                                let offset = offset + i * stride as buffer::Offset;
+                                gl.DrawArraysIndirect(primitive, offset as *const _);
+                            }
+                        }
+                    }
+                } else {
+                    error!("Indirect draw calls are not supported");
+                }
+            }
+            com::Command::DrawIndexedIndirect {
+                primitive,
+                index_type,
+                buffer,
+                offset,
+                draw_count,
+                stride,
+            } => {
+                let gl = &self.share.context;
+                if self
+                    .share
+                    .legacy_features
+                    .contains(LegacyFeatures::INDIRECT_EXECUTION)
+                {
+                    unsafe {
+                        gl.BindBuffer(gl::DRAW_INDIRECT_BUFFER, buffer);
+                        if draw_count > 1 && gl.MultiDrawElementsIndirect.is_loaded() {
+                            gl.MultiDrawElementsIndirect(
+                                primitive,
+                                index_type,
+                                offset as *const _,
+                                draw_count as _,
+                                stride as _,
+                            );
+                        } else {
+                            for i in 0..draw_count as buffer::Offset {
+                                let offset = offset + i * stride as buffer::Offset;
+                                gl.DrawElementsIndirect(primitive, index_type, offset as *const _);
+                            }
+                        }
+                    }
+                } else {
+                    error!("Indirect indexed draw calls are not supported");
+                }
+            }
             com::Command::Dispatch(count) => {
                 // Capability support is given by which queue types will be exposed.
                 // If there is no compute support, this pattern should never be reached
@@ -404,7 +503,7 @@ impl CommandQueue {
                 // because no queue with compute capability can be created.
                 let gl = &self.share.context;
                 unsafe {
-                    gl.BindBuffer(gl::DRAW_INDIRECT_BUFFER, buffer);
+                    gl.BindBuffer(gl::DISPATCH_INDIRECT_BUFFER, buffer);
                     // TODO: possible integer conversion issue
                     gl.DispatchComputeIndirect(offset as _);
                 }
@@ -422,7 +521,7 @@ impl CommandQueue {
                 assert_eq!(num_viewports, depth_ranges.len());
                 assert!(0 < num_viewports && num_viewports <= self.share.limits.max_viewports);
 
-                if num_viewports == 1 {
+                if num_viewports == 1 && first_viewport == 0 {
                     let view = viewports[0];
                     let depth_range = depth_ranges[0];
                     unsafe {
@@ -434,7 +533,7 @@ impl CommandQueue {
                         )
                     };
                     unsafe { gl.DepthRange(depth_range[0], depth_range[1]) };
-                } else if num_viewports > 1 {
+                } else {
                     // Support for these functions is coupled with the support
                     // of multiple viewports.
                     unsafe {
@@ -459,7 +558,7 @@ impl CommandQueue {
                 let num_scissors = scissors.len();
                 assert!(0 < num_scissors && num_scissors <= self.share.limits.max_viewports);
 
-                if num_scissors == 1 {
+                if num_scissors == 1 && first_scissor == 0 {
                     let scissor = scissors[0];
                     unsafe { gl.Scissor(scissor[0], scissor[1], scissor[2], scissor[3]) };
                 } else {
@@ -474,6 +573,34 @@ impl CommandQueue {
                     };
                 }
             }
+            com::Command::SetScissorTest(enable) => {
+                let gl = &self.share.context;
+                if enable {
+                    unsafe { gl.Enable(gl::SCISSOR_TEST) };
+                } else {
+                    unsafe { gl.Disable(gl::SCISSOR_TEST) };
+                }
+            }
+            com::Command::BindStencil(ref stencil) => {
+                state::bind_stencil(&self.share.context, stencil);
+            }
+            com::Command::UnlockColorMask => {
+                state::unlock_color_mask(&self.share.context);
+            }
+            com::Command::UnlockDepthStencilMask => {
+                state::unlock_depth_stencil_mask(&self.share.context);
+            }
+            com::Command::SetDepthBias(bias) => unsafe {
+                self.share
+                    .context
+                    .PolygonOffset(bias.slope_factor as _, bias.const_factor as _);
+            },
+            com::Command::SetLineWidth(width) => unsafe {
+                if width != 1.0 && !self.share.features.contains(hal::Features::LINE_WIDTH) {
+                    warn!("Wide lines are not supported");
+                }
+                self.share.context.LineWidth(width);
+            },
             com::Command::SetBlendColor(color) => {
                 state::set_blend_color(&self.share.context, color);
             }
@@ -523,7 +650,10 @@ impl CommandQueue {
                 if self.share.private_caps.framebuffer {
                     let gl = &self.share.context;
                     unsafe { gl.BindFramebuffer(point, frame_buffer) };
-                    self.state.fbo = frame_buffer;
+                    // Only track the framebuffer we are rendering into.
+                    if point != gl::READ_FRAMEBUFFER {
+                        self.state.fbo = frame_buffer;
+                    }
                 } else if frame_buffer != 0 {
                     error!("Tried to bind FBO {} without FBO support!", frame_buffer);
                 }
@@ -537,11 +667,41 @@ impl CommandQueue {
             com::Command::SetPatchSize(num) => unsafe {
                 self.share.context.PatchParameteri(gl::PATCH_VERTICES, num);
             },
+            com::Command::SetPrimitiveRestart(restart) => {
+                let gl = &self.share.context;
+                let caps = &self.share.private_caps;
+                let index = match restart {
+                    hal::pso::PrimitiveRestart::Disabled => None,
+                    hal::pso::PrimitiveRestart::U16 => Some(u16::max_value() as gl::types::GLuint),
+                    hal::pso::PrimitiveRestart::U32 => Some(u32::max_value()),
+                };
+                match index {
+                    None => Self::disable_primitive_restart(gl, caps),
+                    Some(_) if caps.primitive_restart_fixed_index => unsafe {
+                        gl.Enable(gl::PRIMITIVE_RESTART_FIXED_INDEX);
+                    },
+                    Some(index) if caps.primitive_restart => unsafe {
+                        gl.Enable(gl::PRIMITIVE_RESTART);
+                        gl.PrimitiveRestartIndex(index);
+                    },
+                    Some(_) => error!("Primitive restart is not supported"),
+                }
+            }
             com::Command::BindProgram(program) => unsafe {
                 self.share.context.UseProgram(program);
             },
             com::Command::BindBlendSlot(slot, ref blend) => {
-                state::bind_blend_slot(&self.share.context, slot, blend);
+                if self
+                    .share
+                    .features
+                    .contains(hal::Features::INDEPENDENT_BLENDING)
+                {
+                    state::bind_blend_slot(&self.share.context, slot, blend);
+                } else if slot == 0 {
+                    // All targets share the blend state of the first one,
+                    // as validated at pipeline creation.
+                    state::bind_blend(&self.share.context, blend);
+                }
             }
             com::Command::BindAttribute(ref attribute, handle, stride, rate) => unsafe {
                 use crate::native::VertexAttribFunction::*;
@@ -563,20 +723,24 @@ impl CommandQueue {
                     Float => {
                         gl.VertexAttribPointer(location, size, format, gl::FALSE, stride, offset)
                     }
+                    Normalized => {
+                        gl.VertexAttribPointer(location, size, format, gl::TRUE, stride, offset)
+                    }
                     Integer => gl.VertexAttribIPointer(location, size, format, stride, offset),
                     Double => gl.VertexAttribLPointer(location, size, format, stride, offset),
                 }
 
-                if rate != 0 {
-                    if self
-                        .share
-                        .legacy_features
-                        .contains(LegacyFeatures::INSTANCED_ATTRIBUTE_BINDING)
-                    {
-                        gl.VertexAttribDivisor(location, rate);
-                    } else {
-                        error!("Binding attribute with instanced input rate is not supported");
-                    }
+                // The divisor is part of the attribute state, so it needs to be
+                // reset for per-vertex attributes which were previously bound
+                // to the same location with an instanced input rate.
+                if self
+                    .share
+                    .legacy_features
+                    .contains(LegacyFeatures::INSTANCED_ATTRIBUTE_BINDING)
+                {
+                    gl.VertexAttribDivisor(location, rate);
+                } else if rate != 0 {
+                    error!("Binding attribute with instanced input rate is not supported");
                 }
 
                 gl.EnableVertexAttribArray(location);
@@ -588,96 +752,377 @@ impl CommandQueue {
             }*/
             com::Command::CopyBufferToBuffer(src, dst, ref r) => unsafe {
                 let gl = &self.share.context;
-                gl.BindBuffer(gl::PIXEL_UNPACK_BUFFER, src);
-                gl.BindBuffer(gl::PIXEL_PACK_BUFFER, dst);
+                gl.BindBuffer(gl::COPY_READ_BUFFER, src);
+                gl.BindBuffer(gl::COPY_WRITE_BUFFER, dst);
                 gl.CopyBufferSubData(
-                    gl::PIXEL_UNPACK_BUFFER,
-                    gl::PIXEL_PACK_BUFFER,
+                    gl::COPY_READ_BUFFER,
+                    gl::COPY_WRITE_BUFFER,
                     r.src as _,
                     r.dst as _,
                     r.size as _,
                 );
+                gl.BindBuffer(gl::COPY_READ_BUFFER, 0);
+                gl.BindBuffer(gl::COPY_WRITE_BUFFER, 0);
+            },
+            com::Command::UpdateBuffer(buffer, offset, data_ptr) => unsafe {
+                let gl = &self.share.context;
+                let data = Self::get_raw(data_buf, data_ptr);
+                gl.BindBuffer(gl::COPY_WRITE_BUFFER, buffer);
+                gl.BufferSubData(
+                    gl::COPY_WRITE_BUFFER,
+                    offset as _,
+                    data.len() as _,
+                    data.as_ptr() as *const _,
+                );
+                gl.BindBuffer(gl::COPY_WRITE_BUFFER, 0);
+            },
+            com::Command::CopyBufferToTexture {
+                buffer,
+                texture,
+                target,
+                ref region,
+                format,
+                ty,
+            } => unsafe {
+                // TODO: Fix active texture
+                assert_eq!(region.image_offset.z, 0);
+                let gl = &self.share.context;
+                gl.ActiveTexture(gl::TEXTURE0);
+                gl.BindBuffer(gl::PIXEL_UNPACK_BUFFER, buffer);
+                gl.BindTexture(target, texture);
+                gl.PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+                gl.PixelStorei(gl::UNPACK_ROW_LENGTH, region.buffer_width as _);
+                gl.PixelStorei(gl::UNPACK_IMAGE_HEIGHT, region.buffer_height as _);
+                if target != gl::TEXTURE_2D_ARRAY {
+                    gl.TexSubImage2D(
+                        conv::texture_layer_target(target, region.image_layers.layers.start),
+                        region.image_layers.level as _,
+                        region.image_offset.x,
+                        region.image_offset.y,
+                        region.image_extent.width as _,
+                        region.image_extent.height as _,
+                        format,
+                        ty,
+                        region.buffer_offset as *const _,
+                    );
+                } else {
+                    // Array layers are addressed as the depth of the texture.
+                    let layers = &region.image_layers.layers;
+                    gl.TexSubImage3D(
+                        target,
+                        region.image_layers.level as _,
+                        region.image_offset.x,
+                        region.image_offset.y,
+                        layers.start as _,
+                        region.image_extent.width as _,
+                        region.image_extent.height as _,
+                        (layers.end - layers.start) as _,
+                        format,
+                        ty,
+                        region.buffer_offset as *const _,
+                    );
+                }
+                gl.PixelStorei(gl::UNPACK_IMAGE_HEIGHT, 0);
+                gl.PixelStorei(gl::UNPACK_ROW_LENGTH, 0);
+                gl.PixelStorei(gl::UNPACK_ALIGNMENT, 4);
                 gl.BindBuffer(gl::PIXEL_UNPACK_BUFFER, 0);
-                gl.BindBuffer(gl::PIXEL_PACK_BUFFER, 0);
             },
-            com::Command::CopyBufferToTexture(buffer, texture, ref r) => unsafe {
-                // TODO: Fix format and active texture
-                assert_eq!(r.image_offset.z, 0);
+            com::Command::CopyBufferToCompressedTexture {
+                buffer,
+                texture,
+                target,
+                ref region,
+                format,
+                size,
+            } => unsafe {
+                assert_eq!(region.image_offset.z, 0);
                 let gl = &self.share.context;
                 gl.ActiveTexture(gl::TEXTURE0);
                 gl.BindBuffer(gl::PIXEL_UNPACK_BUFFER, buffer);
-                gl.BindTexture(gl::TEXTURE_2D, texture);
-                gl.TexSubImage2D(
-                    gl::TEXTURE_2D,
-                    r.image_layers.level as _,
-                    r.image_offset.x,
-                    r.image_offset.y,
-                    r.image_extent.width as _,
-                    r.image_extent.height as _,
-                    gl::RGBA,
-                    gl::UNSIGNED_BYTE,
-                    ptr::null(),
-                );
+                gl.BindTexture(target, texture);
+                if target != gl::TEXTURE_2D_ARRAY {
+                    gl.CompressedTexSubImage2D(
+                        conv::texture_layer_target(target, region.image_layers.layers.start),
+                        region.image_layers.level as _,
+                        region.image_offset.x,
+                        region.image_offset.y,
+                        region.image_extent.width as _,
+                        region.image_extent.height as _,
+                        format,
+                        size,
+                        region.buffer_offset as *const _,
+                    );
+                } else {
+                    let layers = &region.image_layers.layers;
+                    gl.CompressedTexSubImage3D(
+                        target,
+                        region.image_layers.level as _,
+                        region.image_offset.x,
+                        region.image_offset.y,
+                        layers.start as _,
+                        region.image_extent.width as _,
+                        region.image_extent.height as _,
+                        (layers.end - layers.start) as _,
+                        format,
+                        size,
+                        region.buffer_offset as *const _,
+                    );
+                }
                 gl.BindBuffer(gl::PIXEL_UNPACK_BUFFER, 0);
             },
             com::Command::CopyBufferToSurface(..) => {
                 unimplemented!() //TODO: use FBO
             }
-            com::Command::CopyTextureToBuffer(texture, buffer, ref r) => unsafe {
-                // TODO: Fix format and active texture
-                // TODO: handle partial copies gracefully
-                assert_eq!(r.image_offset, hal::image::Offset { x: 0, y: 0, z: 0 });
+            com::Command::CopyTextureToBuffer {
+                texture,
+                target,
+                buffer,
+                ref region,
+                format,
+                ty,
+            } => unsafe {
                 let gl = &self.share.context;
                 gl.ActiveTexture(gl::TEXTURE0);
                 gl.BindBuffer(gl::PIXEL_PACK_BUFFER, buffer);
-                gl.BindTexture(gl::TEXTURE_2D, texture);
+                gl.BindTexture(target, texture);
+                gl.PixelStorei(gl::PACK_ALIGNMENT, 1);
+                gl.PixelStorei(gl::PACK_ROW_LENGTH, region.buffer_width as _);
                 gl.GetTexImage(
-                    gl::TEXTURE_2D,
-                    r.image_layers.level as _,
-                    //r.image_offset.x, r.image_offset.y,
-                    //r.image_extent.width as _, r.image_extent.height as _,
-                    gl::RGBA,
-                    gl::UNSIGNED_BYTE,
-                    ptr::null_mut(),
+                    conv::texture_layer_target(target, region.image_layers.layers.start),
+                    region.image_layers.level as _,
+                    format,
+                    ty,
+                    region.buffer_offset as *mut _,
                 );
+                gl.PixelStorei(gl::PACK_ROW_LENGTH, 0);
+                gl.PixelStorei(gl::PACK_ALIGNMENT, 4);
                 gl.BindBuffer(gl::PIXEL_PACK_BUFFER, 0);
             },
-            com::Command::CopySurfaceToBuffer(..) => {
-                unimplemented!() //TODO: use FBO
-            }
+            com::Command::CopyReadAttachmentToBuffer {
+                attachment,
+                buffer,
+                ref region,
+                format,
+                ty,
+            } => unsafe {
+                let gl = &self.share.context;
+                if attachment != gl::DEPTH_ATTACHMENT && attachment != gl::STENCIL_ATTACHMENT {
+                    gl.ReadBuffer(attachment);
+                }
+                // Rows are tightly packed unless `buffer_width` specifies
+                // a larger row length, matching the layout of other backends.
+                gl.BindBuffer(gl::PIXEL_PACK_BUFFER, buffer);
+                gl.PixelStorei(gl::PACK_ALIGNMENT, 1);
+                gl.PixelStorei(gl::PACK_ROW_LENGTH, region.buffer_width as _);
+                gl.ReadPixels(
+                    region.image_offset.x,
+                    region.image_offset.y,
+                    region.image_extent.width as _,
+                    region.image_extent.height as _,
+                    format,
+                    ty,
+                    region.buffer_offset as *mut _,
+                );
+                gl.PixelStorei(gl::PACK_ROW_LENGTH, 0);
+                gl.PixelStorei(gl::PACK_ALIGNMENT, 4);
+                gl.BindBuffer(gl::PIXEL_PACK_BUFFER, 0);
+                self.unbind_target(gl::READ_FRAMEBUFFER, attachment);
+            },
+            com::Command::BlitFrameBuffer {
+                ref src_bounds,
+                ref dst_bounds,
+                mask,
+                filter,
+            } => unsafe {
+                let gl = &self.share.context;
+                if mask & gl::COLOR_BUFFER_BIT != 0 {
+                    gl.ReadBuffer(gl::COLOR_ATTACHMENT0);
+                }
+                gl.BlitFramebuffer(
+                    src_bounds.start.x,
+                    src_bounds.start.y,
+                    src_bounds.end.x,
+                    src_bounds.end.y,
+                    dst_bounds.start.x,
+                    dst_bounds.start.y,
+                    dst_bounds.end.x,
+                    dst_bounds.end.y,
+                    mask,
+                    filter,
+                );
+            },
             com::Command::CopyImageToTexture(..) => {
                 unimplemented!() //TODO: use FBO
             }
             com::Command::CopyImageToSurface(..) => {
                 unimplemented!() //TODO: use FBO
             }
+            com::Command::BeginQuery(query, flags) => unsafe {
+                let target = self.occlusion_query_target(flags);
+                self.share.context.BeginQuery(target, query);
+            },
+            com::Command::EndQuery(flags) => unsafe {
+                let target = self.occlusion_query_target(flags);
+                self.share.context.EndQuery(target);
+            },
+            com::Command::WriteTimestamp(query) => unsafe {
+                self.share.context.QueryCounter(query, gl::TIMESTAMP);
+            },
+            com::Command::CopyQueryResult(query, buffer, offset, flags) => unsafe {
+                let gl = &self.share.context;
+                let bits_64 = flags.contains(hal::query::ResultFlags::BITS_64);
+                let value_size = if bits_64 { 8 } else { 4 };
+
+                // Query buffers can't write intermediate results, partial
+                // reads go through the host instead.
+                if self.share.private_caps.query_buffer
+                    && !flags.contains(hal::query::ResultFlags::PARTIAL)
+                {
+                    let get = |pname, offset: u64| {
+                        if bits_64 {
+                            gl.GetQueryObjectui64v(query, pname, offset as *mut _);
+                        } else {
+                            gl.GetQueryObjectuiv(query, pname, offset as *mut _);
+                        }
+                    };
+                    gl.BindBuffer(gl::QUERY_BUFFER, buffer);
+                    if flags.contains(hal::query::ResultFlags::WAIT) {
+                        get(gl::QUERY_RESULT, offset);
+                    } else {
+                        get(gl::QUERY_RESULT_NO_WAIT, offset);
+                    }
+                    if flags.contains(hal::query::ResultFlags::WITH_AVAILABILITY) {
+                        get(gl::QUERY_RESULT_AVAILABLE, offset + value_size);
+                    }
+                    gl.BindBuffer(gl::QUERY_BUFFER, 0);
+                } else {
+                    let result = device::query_result(gl, query, flags);
+                    let mut data = SmallVec::<[u8; 16]>::new();
+                    let mut push_value = |value: u64| {
+                        if bits_64 {
+                            data.extend_from_slice(&value.to_ne_bytes());
+                        } else {
+                            data.extend_from_slice(&(value as u32).to_ne_bytes());
+                        }
+                    };
+                    // Unavailable results are only written for partial reads.
+                    let mut start = offset;
+                    match result {
+                        Some(value) => push_value(value),
+                        None if flags.contains(hal::query::ResultFlags::PARTIAL) => push_value(0),
+                        None => start += value_size,
+                    }
+                    if flags.contains(hal::query::ResultFlags::WITH_AVAILABILITY) {
+                        push_value(result.is_some() as u64);
+                    }
+
+                    if !data.is_empty() {
+                        gl.BindBuffer(gl::COPY_WRITE_BUFFER, buffer);
+                        gl.BufferSubData(
+                            gl::COPY_WRITE_BUFFER,
+                            start as _,
+                            data.len() as _,
+                            data.as_ptr() as *const _,
+                        );
+                        gl.BindBuffer(gl::COPY_WRITE_BUFFER, 0);
+                    }
+                }
+            },
             com::Command::BindBufferRange(target, index, buffer, offset, size) => unsafe {
                 let gl = &self.share.context;
                 gl.BindBufferRange(target, index, buffer, offset, size);
             },
-            com::Command::BindTexture(index, texture) => unsafe {
+            com::Command::BindTexture(index, texture, target) => unsafe {
                 let gl = &self.share.context;
                 gl.ActiveTexture(gl::TEXTURE0 + index);
-                gl.BindTexture(gl::TEXTURE_2D, texture);
+                gl.BindTexture(target, texture);
+            },
+            com::Command::SetTextureLevels(target, start, end) => unsafe {
+                let gl = &self.share.context;
+                gl.TexParameteri(target, gl::TEXTURE_BASE_LEVEL, start as _);
+                gl.TexParameteri(target, gl::TEXTURE_MAX_LEVEL, (end - 1) as _);
+            },
+            com::Command::GenerateMipmap(texture, target, ref levels, num_levels) => unsafe {
+                // TODO: Fix active texture
+                let gl = &self.share.context;
+                gl.ActiveTexture(gl::TEXTURE0);
+                gl.BindTexture(target, texture);
+                // Levels are generated from the base up to the maximum level.
+                gl.TexParameteri(target, gl::TEXTURE_BASE_LEVEL, levels.start as _);
+                gl.TexParameteri(target, gl::TEXTURE_MAX_LEVEL, (levels.end - 1) as _);
+                gl.GenerateMipmap(target);
+                gl.TexParameteri(target, gl::TEXTURE_BASE_LEVEL, 0);
+                gl.TexParameteri(target, gl::TEXTURE_MAX_LEVEL, (num_levels - 1) as _);
+            },
+            com::Command::BindImageTexture {
+                unit,
+                texture,
+                level,
+                layer,
+                format,
+            } => unsafe {
+                let gl = &self.share.context;
+                gl.BindImageTexture(
+                    unit,
+                    texture,
+                    level as _,
+                    if layer.is_some() { gl::FALSE } else { gl::TRUE },
+                    layer.unwrap_or(0) as _,
+                    gl::READ_WRITE,
+                    format,
+                );
             },
             com::Command::BindSampler(index, sampler) => unsafe {
                 let gl = &self.share.context;
                 gl.BindSampler(index, sampler);
             },
-            com::Command::SetTextureSamplerSettings(index, texture, ref sinfo) => unsafe {
+            com::Command::SetTextureSamplerSettings(index, texture, target, ref sinfo) => unsafe {
                 let gl = &self.share.context;
                 gl.ActiveTexture(gl::TEXTURE0 + index);
-                gl.BindTexture(gl::TEXTURE_2D, texture);
+                gl.BindTexture(target, texture);
 
                 // TODO: Optimization: only change texture properties that have changed.
                 device::set_sampler_info(
                     &self.share,
                     &sinfo,
-                    |a, b| gl.TexParameterf(gl::TEXTURE_2D, a, b),
-                    |a, b| gl.TexParameterfv(gl::TEXTURE_2D, a, &b[0]),
-                    |a, b| gl.TexParameteri(gl::TEXTURE_2D, a, b),
+                    |a, b| gl.TexParameterf(target, a, b),
+                    |a, b| gl.TexParameterfv(target, a, &b[0]),
+                    |a, b| gl.TexParameteri(target, a, b),
                 );
-            }, /*
+            },
+            com::Command::PushDebugGroup(name_ptr) => {
+                if self.share.private_caps.debug_markers {
+                    let name = Self::get_raw(data_buf, name_ptr);
+                    unsafe {
+                        self.share.context.PushDebugGroup(
+                            gl::DEBUG_SOURCE_APPLICATION,
+                            0,
+                            name.len() as _,
+                            name.as_ptr() as *const _,
+                        );
+                    }
+                }
+            }
+            com::Command::PopDebugGroup => {
+                if self.share.private_caps.debug_markers {
+                    unsafe { self.share.context.PopDebugGroup() };
+                }
+            }
+            com::Command::InsertDebugMarker(name_ptr) => {
+                if self.share.private_caps.debug_markers {
+                    let name = Self::get_raw(data_buf, name_ptr);
+                    unsafe {
+                        self.share.context.DebugMessageInsert(
+                            gl::DEBUG_SOURCE_APPLICATION,
+                            gl::DEBUG_TYPE_MARKER,
+                            0,
+                            gl::DEBUG_SEVERITY_NOTIFICATION,
+                            name.len() as _,
+                            name.as_ptr() as *const _,
+                        );
+                    }
+                }
+            } /*
             com::Command::BindConstantBuffer(pso::ConstantBufferParam(buffer, _, slot)) => unsafe {
             self.share.context.BindBufferBase(gl::UNIFORM_BUFFER, slot as gl::types::GLuint, buffer);
             },
@@ -760,6 +1205,39 @@ impl CommandQueue {
                             let data = Self::get::<[i32; 4]>(data_buf, buffer);
                             gl.Uniform4iv(uniform.location as _, 1, data[0].as_ptr() as _);
                         }
+                        gl::UNSIGNED_INT => {
+                            let data = Self::get::<u32>(data_buf, buffer);
+                            gl.Uniform1uiv(uniform.location as _, 1, data.as_ptr() as _);
+                        }
+                        gl::UNSIGNED_INT_VEC2 => {
+                            let data = Self::get::<[u32; 2]>(data_buf, buffer);
+                            gl.Uniform2uiv(uniform.location as _, 1, data[0].as_ptr() as _);
+                        }
+                        gl::UNSIGNED_INT_VEC3 => {
+                            let data = Self::get::<[u32; 3]>(data_buf, buffer);
+                            gl.Uniform3uiv(uniform.location as _, 1, data[0].as_ptr() as _);
+                        }
+                        gl::UNSIGNED_INT_VEC4 => {
+                            let data = Self::get::<[u32; 4]>(data_buf, buffer);
+                            gl.Uniform4uiv(uniform.location as _, 1, data[0].as_ptr() as _);
+                        }
+                        // Booleans are 32 bits wide and set through the integer variants.
+                        gl::BOOL => {
+                            let data = Self::get::<i32>(data_buf, buffer);
+                            gl.Uniform1iv(uniform.location as _, 1, data.as_ptr() as _);
+                        }
+                        gl::BOOL_VEC2 => {
+                            let data = Self::get::<[i32; 2]>(data_buf, buffer);
+                            gl.Uniform2iv(uniform.location as _, 1, data[0].as_ptr() as _);
+                        }
+                        gl::BOOL_VEC3 => {
+                            let data = Self::get::<[i32; 3]>(data_buf, buffer);
+                            gl.Uniform3iv(uniform.location as _, 1, data[0].as_ptr() as _);
+                        }
+                        gl::BOOL_VEC4 => {
+                            let data = Self::get::<[i32; 4]>(data_buf, buffer);
+                            gl.Uniform4iv(uniform.location as _, 1, data[0].as_ptr() as _);
+                        }
                         gl::FLOAT_MAT2 => {
                             let data = Self::get::<[f32; 4]>(data_buf, buffer);
                             gl.UniformMatrix2fv(
@@ -828,20 +1306,77 @@ impl CommandQueue {
                     Fill => (gl::FILL, gl::POLYGON_OFFSET_FILL),
                 };
 
-                unsafe { gl.PolygonMode(gl::FRONT_AND_BACK, gl_draw) };
+                // GLES only rasterizes filled polygons.
+                if self
+                    .share
+                    .features
+                    .contains(hal::Features::NON_FILL_POLYGON_MODE)
+                {
+                    unsafe { gl.PolygonMode(gl::FRONT_AND_BACK, gl_draw) };
+
+                    // Disable the offsets of the other polygon modes,
+                    // a previous pipeline might have enabled them.
+                    for &offset in &[
+                        gl::POLYGON_OFFSET_POINT,
+                        gl::POLYGON_OFFSET_LINE,
+                        gl::POLYGON_OFFSET_FILL,
+                    ] {
+                        if offset != gl_offset {
+                            unsafe { gl.Disable(offset) };
+                        }
+                    }
+                }
 
                 match rasterizer.depth_bias {
                     Some(hal::pso::State::Static(bias)) => unsafe {
                         gl.Enable(gl_offset);
                         gl.PolygonOffset(bias.slope_factor as _, bias.const_factor as _);
                     },
-                    _ => unsafe { gl.Disable(gl_offset) },
+                    // Values are provided by `SetDepthBias`.
+                    Some(hal::pso::State::Dynamic) => unsafe { gl.Enable(gl_offset) },
+                    None => unsafe { gl.Disable(gl_offset) },
+                }
+            }
+            com::Command::BindMultisampling(ref multisampling) => {
+                let gl = &self.share.context;
+                // GLES has no switch for multisampled rasterization.
+                if !self.share.info.version.is_embedded {
+                    if multisampling.is_some() {
+                        unsafe { gl.Enable(gl::MULTISAMPLE) };
+                    } else {
+                        unsafe { gl.Disable(gl::MULTISAMPLE) };
+                    }
+                }
+                let alpha_coverage = multisampling.as_ref().map_or(false, |ms| ms.alpha_coverage);
+                if alpha_coverage {
+                    unsafe { gl.Enable(gl::SAMPLE_ALPHA_TO_COVERAGE) };
+                } else {
+                    unsafe { gl.Disable(gl::SAMPLE_ALPHA_TO_COVERAGE) };
                 }
 
-                match false {
-                    //TODO
-                    true => unsafe { gl.Enable(gl::MULTISAMPLE) },
-                    false => unsafe { gl.Disable(gl::MULTISAMPLE) },
+                if self.share.private_caps.sample_mask {
+                    match multisampling {
+                        Some(ref ms) if ms.sample_mask != !0 => unsafe {
+                            gl.Enable(gl::SAMPLE_MASK);
+                            // GL only exposes the first 32 samples.
+                            gl.SampleMaski(0, ms.sample_mask as u32);
+                        },
+                        _ => unsafe { gl.Disable(gl::SAMPLE_MASK) },
+                    }
+                }
+
+                if self
+                    .share
+                    .features
+                    .contains(hal::Features::SAMPLE_RATE_SHADING)
+                {
+                    match multisampling.as_ref().and_then(|ms| ms.sample_shading) {
+                        Some(min_sample_shading) => unsafe {
+                            gl.Enable(gl::SAMPLE_SHADING);
+                            gl.MinSampleShading(min_sample_shading);
+                        },
+                        None => unsafe { gl.Disable(gl::SAMPLE_SHADING) },
+                    }
                 }
             }
             com::Command::BindDepth { depth } => {
@@ -39,29 +39,20 @@ fn map_operation(op: pso::StencilOp) -> gl::types::GLenum {
     }
 }
 
-pub(crate) fn bind_stencil(
-    gl: &GlContainer,
-    stencil: &pso::StencilTest,
-    (ref_front, ref_back): (pso::StencilValue, pso::StencilValue),
-    cull: Option<pso::Face>,
-) {
-    fn bind_side(
-        gl: &GlContainer,
-        face: gl::types::GLenum,
-        side: &pso::StencilFace,
-        ref_value: pso::StencilValue,
-    ) {
+pub(crate) fn bind_stencil(gl: &GlContainer, stencil: &pso::StencilTest) {
+    fn bind_side(gl: &GlContainer, face: gl::types::GLenum, side: &pso::StencilFace) {
+        let value = |state: pso::State<pso::StencilValue>, default| match state {
+            pso::State::Static(v) => v,
+            pso::State::Dynamic => default,
+        };
         unsafe {
-            let mr = match side.mask_read {
-                pso::State::Static(v) => v,
-                pso::State::Dynamic => !0,
-            };
-            let mw = match side.mask_write {
-                pso::State::Static(v) => v,
-                pso::State::Dynamic => !0,
-            };
-            gl.StencilFuncSeparate(face, map_comparison(side.fun), ref_value as _, mr);
-            gl.StencilMaskSeparate(face, mw);
+            gl.StencilFuncSeparate(
+                face,
+                map_comparison(side.fun),
+                value(side.reference, 0) as _,
+                value(side.mask_read, !0),
+            );
+            gl.StencilMaskSeparate(face, value(side.mask_write, !0));
             gl.StencilOpSeparate(
                 face,
                 map_operation(side.op_fail),
@@ -76,14 +67,8 @@ pub(crate) fn bind_stencil(
             ref back,
         } => {
             unsafe { gl.Enable(gl::STENCIL_TEST) };
-            if let Some(cf) = cull {
-                if !cf.contains(pso::Face::FRONT) {
-                    bind_side(gl, gl::FRONT, front, ref_front);
-                }
-                if !cf.contains(pso::Face::BACK) {
-                    bind_side(gl, gl::BACK, back, ref_back);
-                }
-            }
+            bind_side(gl, gl::FRONT, front);
+            bind_side(gl, gl::BACK, back);
         }
         pso::StencilTest::Off => unsafe {
             gl.Disable(gl::STENCIL_TEST);
@@ -188,6 +173,13 @@ pub(crate) fn unlock_color_mask(gl: &GlContainer) {
     unsafe { gl.ColorMask(gl::TRUE, gl::TRUE, gl::TRUE, gl::TRUE) };
 }
 
+pub(crate) fn unlock_depth_stencil_mask(gl: &GlContainer) {
+    unsafe {
+        gl.DepthMask(gl::TRUE);
+        gl.StencilMask(!0);
+    };
+}
+
 pub(crate) fn set_blend_color(gl: &GlContainer, color: pso::ColorValue) {
     unsafe { gl.BlendColor(color[0], color[1], color[2], color[3]) };
 }
@@ -46,7 +46,7 @@
 use crate::hal::window::Extent2D;
 use crate::hal::{self, format as f, image, memory, CompositeAlpha};
 
-use crate::{native, Backend as B, Device, PhysicalDevice, QueueFamily, Starc};
+use crate::{conv, native, Backend as B, Device, PhysicalDevice, QueueFamily, Starc};
 
 use glutin::{self, ContextTrait};
 
@@ -68,6 +68,8 @@ pub struct Swapchain {
     pub(crate) window: Starc<glutin::WindowedContext>,
     // Extent because the window lies
     pub(crate) extent: Extent2D,
+    // Backing storage of the images, released with the swapchain
+    pub(crate) images: Vec<native::ImageKind>,
 }
 
 impl hal::Swapchain<B> for Swapchain {
@@ -171,17 +173,18 @@ impl Device {
         surface: &mut Surface,
         config: hal::SwapchainConfig,
     ) -> (Swapchain, Vec<native::Image>) {
-        let swapchain = Swapchain {
-            extent: config.extent,
-            window: surface.window.clone(),
-        };
+        // The default framebuffer has to follow the size of the window, which
+        // is not done automatically on all platforms.
+        surface.window.resize(glutin::dpi::PhysicalSize::new(
+            config.extent.width as f64,
+            config.extent.height as f64,
+        ));
 
         let gl = &self.share.context;
 
-        let (int_format, iformat, itype) = match config.format {
-            f::Format::Rgba8Unorm => (gl::RGBA8, gl::RGBA, gl::UNSIGNED_BYTE),
-            f::Format::Rgba8Srgb => (gl::SRGB8_ALPHA8, gl::RGBA, gl::UNSIGNED_BYTE),
-            _ => unimplemented!(),
+        let (int_format, iformat, itype) = match conv::image_format_to_gl(config.format) {
+            Some(f) => f,
+            None => unimplemented!(),
         };
 
         let channel = config.format.base_format().1;
@@ -234,7 +237,7 @@ impl Device {
                             }
                         }
                     };
-                    native::ImageKind::Texture(name)
+                    native::ImageKind::Texture(name, gl::TEXTURE_2D)
                 } else {
                     let mut name = 0;
                     gl.GenRenderbuffers(1, &mut name);
@@ -264,6 +267,10 @@ impl Device {
 
                 native::Image {
                     kind: image,
+                    format: config.format,
+                    extent: config.extent.to_extent(),
+                    num_levels: 1,
+                    num_layers: 1,
                     channel,
                     requirements: memory::Requirements {
                         size,
@@ -274,6 +281,12 @@ impl Device {
             })
             .collect::<Vec<_>>();
 
+        let swapchain = Swapchain {
+            extent: config.extent,
+            window: surface.window.clone(),
+            images: images.iter().map(|image| image.kind).collect(),
+        };
+
         (swapchain, images)
     }
 }
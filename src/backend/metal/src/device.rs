@@ -467,6 +467,8 @@ impl hal::PhysicalDevice<Backend> for PhysicalDevice {
             non_coherent_atom_size: 4,
             max_sampler_anisotropy: 16.,
             min_vertex_input_binding_stride_alignment: STRIDE_GRANULARITY as u64,
+            // `write_timestamp` records nothing, so there is no period to report.
+            timestamp_period: 0.0,
 
             .. hal::Limits::default() // TODO!
         }
@@ -2,6 +2,7 @@ use ash::version::DeviceV1_0;
 use ash::vk;
 use smallvec::SmallVec;
 use std::borrow::Borrow;
+use std::ffi::CString;
 use std::ops::Range;
 use std::sync::Arc;
 use std::{mem, ptr};
@@ -20,6 +21,16 @@ pub struct CommandBuffer {
     pub device: Arc<RawDevice>,
 }
 
+fn debug_label(name: &CString, color: u32) -> vk::DebugUtilsLabelEXT {
+    let channel = |shift: u32| ((color >> shift) & 0xFF) as f32 / 255.0;
+    vk::DebugUtilsLabelEXT {
+        s_type: vk::StructureType::DEBUG_UTILS_LABEL_EXT,
+        p_next: ptr::null(),
+        p_label_name: name.as_ptr(),
+        color: [channel(24), channel(16), channel(8), channel(0)],
+    }
+}
+
 fn map_subpass_contents(contents: com::SubpassContents) -> vk::SubpassContents {
     match contents {
         com::SubpassContents::Inline => vk::SubpassContents::INLINE,
@@ -889,4 +900,24 @@ impl com::RawCommandBuffer<Backend> for CommandBuffer {
             .0
             .cmd_execute_commands(self.raw, &command_buffers);
     }
+
+    unsafe fn begin_debug_marker(&mut self, name: &str, color: u32) {
+        if let Some(ref debug_utils) = self.device.2 {
+            let name = conv::map_debug_name(name);
+            debug_utils.cmd_begin_debug_utils_label(self.raw, &debug_label(&name, color));
+        }
+    }
+
+    unsafe fn end_debug_marker(&mut self) {
+        if let Some(ref debug_utils) = self.device.2 {
+            debug_utils.cmd_end_debug_utils_label(self.raw);
+        }
+    }
+
+    unsafe fn insert_debug_marker(&mut self, name: &str, color: u32) {
+        if let Some(ref debug_utils) = self.device.2 {
+            let name = conv::map_debug_name(name);
+            debug_utils.cmd_insert_debug_utils_label(self.raw, &debug_label(&name, color));
+        }
+    }
 }
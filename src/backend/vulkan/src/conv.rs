@@ -6,6 +6,7 @@ use hal::{CompositeAlpha, Features, IndexType, PresentMode, Primitive};
 
 use native as n;
 use std::borrow::Borrow;
+use std::ffi::CString;
 use std::mem;
 use std::ptr;
 
@@ -577,3 +578,10 @@ pub fn map_descriptor_pool_create_flags(
 ) -> vk::DescriptorPoolCreateFlags {
     vk::DescriptorPoolCreateFlags::from_raw(flags.bits())
 }
+
+/// Convert a debug label to a C string. Vulkan names are NUL-terminated, so
+/// anything after an interior NUL is dropped instead of failing.
+pub fn map_debug_name(name: &str) -> CString {
+    let name = name.split('\0').next().unwrap_or("");
+    CString::new(name).unwrap_or_default()
+}
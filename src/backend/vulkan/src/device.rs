@@ -1,6 +1,7 @@
 use ash::extensions::khr;
 use ash::version::DeviceV1_0;
 use ash::vk;
+use ash::vk::Handle;
 use smallvec::SmallVec;
 
 use hal;
@@ -514,7 +515,10 @@ impl d::Device<B> for Device {
                     p_next: ptr::null(),
                     flags: vk::PipelineInputAssemblyStateCreateFlags::empty(),
                     topology: conv::map_topology(desc.input_assembler.primitive),
-                    primitive_restart_enable: vk::FALSE,
+                    primitive_restart_enable: match desc.input_assembler.primitive_restart {
+                        pso::PrimitiveRestart::Disabled => vk::FALSE,
+                        _ => vk::TRUE,
+                    },
                 });
                 let depth_bias = match desc.rasterizer.depth_bias {
                     Some(pso::State::Static(db)) => db,
@@ -646,11 +650,26 @@ impl d::Device<B> for Device {
                     pso::StencilTest::On {
                         ref front,
                         ref back,
-                    } => (
-                        vk::TRUE,
-                        conv::map_stencil_side(front),
-                        conv::map_stencil_side(back),
-                    ),
+                    } => {
+                        let is_dynamic = |value: fn(&pso::StencilFace) -> _| {
+                            value(front) == pso::State::Dynamic
+                                || value(back) == pso::State::Dynamic
+                        };
+                        if is_dynamic(|f| f.mask_read) {
+                            dynamic_states.push(vk::DynamicState::STENCIL_COMPARE_MASK);
+                        }
+                        if is_dynamic(|f| f.mask_write) {
+                            dynamic_states.push(vk::DynamicState::STENCIL_WRITE_MASK);
+                        }
+                        if is_dynamic(|f| f.reference) {
+                            dynamic_states.push(vk::DynamicState::STENCIL_REFERENCE);
+                        }
+                        (
+                            vk::TRUE,
+                            conv::map_stencil_side(front),
+                            conv::map_stencil_side(back),
+                        )
+                    }
                     pso::StencilTest::Off => mem::zeroed(),
                 };
                 let (min_depth_bounds, max_depth_bounds) = match desc.baked_states.depth_bounds {
@@ -2043,6 +2062,78 @@ impl d::Device<B> for Device {
                 .map_err(From::<result::Error>::from)
         }
     }
+
+    unsafe fn set_image_name(&self, image: &mut n::Image, name: &str) {
+        self.set_object_name(vk::ObjectType::IMAGE, image.raw.as_raw(), name)
+    }
+
+    unsafe fn set_buffer_name(&self, buffer: &mut n::Buffer, name: &str) {
+        self.set_object_name(vk::ObjectType::BUFFER, buffer.raw.as_raw(), name)
+    }
+
+    unsafe fn set_command_buffer_name(
+        &self,
+        command_buffer: &mut ::command::CommandBuffer,
+        name: &str,
+    ) {
+        self.set_object_name(
+            vk::ObjectType::COMMAND_BUFFER,
+            command_buffer.raw.as_raw(),
+            name,
+        )
+    }
+
+    unsafe fn set_semaphore_name(&self, semaphore: &mut n::Semaphore, name: &str) {
+        self.set_object_name(vk::ObjectType::SEMAPHORE, semaphore.0.as_raw(), name)
+    }
+
+    unsafe fn set_fence_name(&self, fence: &mut n::Fence, name: &str) {
+        self.set_object_name(vk::ObjectType::FENCE, fence.0.as_raw(), name)
+    }
+
+    unsafe fn set_framebuffer_name(&self, framebuffer: &mut n::Framebuffer, name: &str) {
+        self.set_object_name(vk::ObjectType::FRAMEBUFFER, framebuffer.raw.as_raw(), name)
+    }
+
+    unsafe fn set_render_pass_name(&self, render_pass: &mut n::RenderPass, name: &str) {
+        self.set_object_name(vk::ObjectType::RENDER_PASS, render_pass.raw.as_raw(), name)
+    }
+
+    unsafe fn set_descriptor_set_name(&self, descriptor_set: &mut n::DescriptorSet, name: &str) {
+        self.set_object_name(
+            vk::ObjectType::DESCRIPTOR_SET,
+            descriptor_set.raw.as_raw(),
+            name,
+        )
+    }
+
+    unsafe fn set_descriptor_set_layout_name(
+        &self,
+        descriptor_set_layout: &mut n::DescriptorSetLayout,
+        name: &str,
+    ) {
+        self.set_object_name(
+            vk::ObjectType::DESCRIPTOR_SET_LAYOUT,
+            descriptor_set_layout.raw.as_raw(),
+            name,
+        )
+    }
+}
+
+impl Device {
+    unsafe fn set_object_name(&self, object_type: vk::ObjectType, object_handle: u64, name: &str) {
+        if let Some(ref debug_utils) = self.raw.2 {
+            let name = conv::map_debug_name(name);
+            let info = vk::DebugUtilsObjectNameInfoEXT {
+                s_type: vk::StructureType::DEBUG_UTILS_OBJECT_NAME_INFO_EXT,
+                p_next: ptr::null(),
+                object_type,
+                object_handle,
+                p_object_name: name.as_ptr(),
+            };
+            let _ = debug_utils.debug_utils_set_object_name(self.raw.0.handle(), &info);
+        }
+    }
 }
 
 #[test]
@@ -546,8 +546,14 @@ impl hal::PhysicalDevice<Backend> for PhysicalDevice {
             )
         });
 
+        let debug_utils = self
+            .instance
+            .1
+            .as_ref()
+            .map(|&(ref debug_utils, _)| debug_utils.clone());
+
         let device = Device {
-            raw: Arc::new(RawDevice(device_raw, requested_features)),
+            raw: Arc::new(RawDevice(device_raw, requested_features, debug_utils)),
         };
 
         let device_arc = device.raw.clone();
@@ -841,6 +847,7 @@ impl hal::PhysicalDevice<Backend> for PhysicalDevice {
             buffer_image_granularity: limits.buffer_image_granularity,
             non_coherent_atom_size: limits.non_coherent_atom_size as _,
             max_sampler_anisotropy: limits.max_sampler_anisotropy,
+            timestamp_period: limits.timestamp_period,
             min_vertex_input_binding_stride_alignment: 1,
             .. Limits::default() //TODO: please halp
         }
@@ -893,7 +900,7 @@ impl hal::PhysicalDevice<Backend> for PhysicalDevice {
 }
 
 #[doc(hidden)]
-pub struct RawDevice(pub ash::Device, Features);
+pub struct RawDevice(pub ash::Device, Features, Option<ext::DebugUtils>);
 impl fmt::Debug for RawDevice {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "RawDevice") // TODO: Real Debug impl
@@ -318,6 +318,17 @@ impl<B: Backend, C: Supports<Graphics>, S: Shot, L: Level> CommandBuffer<B, C, S
         self.raw
             .blit_image(src, src_layout, dst, dst_layout, filter, regions)
     }
+
+    /// Identical to the `RawCommandBuffer` method of the same name.
+    pub unsafe fn generate_mipmaps(
+        &mut self,
+        image: &B::Image,
+        extent: image::Extent,
+        levels: Range<image::Level>,
+        layers: Range<image::Layer>,
+    ) {
+        self.raw.generate_mipmaps(image, extent, levels, layers)
+    }
 }
 
 impl<B: Backend, C: Supports<Graphics>, S: Shot> CommandBuffer<B, C, S, Primary> {
@@ -1,13 +1,15 @@
 use std::any::Any;
 use std::borrow::Borrow;
 use std::fmt;
+use std::iter;
 use std::ops::Range;
 
 use super::{
     AttachmentClear, BufferCopy, BufferImageCopy, ImageBlit, ImageCopy, ImageResolve,
     SubpassContents,
 };
-use crate::image::{Filter, Layout, SubresourceRange};
+use crate::format::Aspects;
+use crate::image::{self, Filter, Layout, SubresourceRange};
 use crate::memory::{Barrier, Dependencies};
 use crate::range::RangeArg;
 use crate::{buffer, pass, pso, query};
@@ -203,6 +205,54 @@ pub trait RawCommandBuffer<B: Backend>: fmt::Debug + Any + Send + Sync {
         T: IntoIterator,
         T::Item: Borrow<ImageBlit>;
 
+    /// Fill the mipmap levels of a color image from the first one in `levels`
+    /// by downsampling each level into the next with a linear filter, for
+    /// example after rendering into it.
+    ///
+    /// `extent` is the extent of level 0. The image has to be in the `General`
+    /// layout, with the first of `levels` made visible to transfer reads. The
+    /// other levels are left in the `TRANSFER_WRITE` state. The format needs
+    /// to support linear filtered blits.
+    ///
+    /// By default the levels are generated with `blit_image`.
+    unsafe fn generate_mipmaps(
+        &mut self,
+        image: &B::Image,
+        extent: image::Extent,
+        levels: Range<image::Level>,
+        layers: Range<image::Layer>,
+    ) {
+        for level in levels.clone().skip(1) {
+            // The previous level was written by the last blit.
+            if level - 1 > levels.start {
+                let barrier = Barrier::Image {
+                    states: (image::Access::TRANSFER_WRITE, Layout::General)
+                        ..(image::Access::TRANSFER_READ, Layout::General),
+                    target: image,
+                    families: None,
+                    range: SubresourceRange {
+                        aspects: Aspects::COLOR,
+                        levels: level - 1..level,
+                        layers: layers.clone(),
+                    },
+                };
+                self.pipeline_barrier(
+                    pso::PipelineStage::TRANSFER..pso::PipelineStage::TRANSFER,
+                    Dependencies::empty(),
+                    iter::once(barrier),
+                );
+            }
+            self.blit_image(
+                image,
+                Layout::General,
+                image,
+                Layout::General,
+                Filter::Linear,
+                iter::once(mip_blit(extent, level, layers.clone())),
+            );
+        }
+    }
+
     /// Bind the index buffer view, making it the "current" one that draw commands
     /// will operate on.
     unsafe fn bind_index_buffer(&mut self, view: buffer::IndexBufferView<B>);
@@ -538,6 +588,24 @@ pub trait RawCommandBuffer<B: Backend>: fmt::Debug + Any + Send + Sync {
     where
         T: 'a + Borrow<B::CommandBuffer>,
         I: IntoIterator<Item = &'a T>;
+
+    /// Open a labeled group of commands for debugging tools.
+    ///
+    /// The color is given as `0xRRGGBBAA`. Groups can be nested and each
+    /// one must be closed with `end_debug_marker` in the same command buffer.
+    /// Backends without support for debug annotations ignore this, which is also
+    /// what the default implementation does.
+    unsafe fn begin_debug_marker(&mut self, _name: &str, _color: u32) {}
+
+    /// Close the group opened by the last `begin_debug_marker`.
+    unsafe fn end_debug_marker(&mut self) {}
+
+    /// Insert a single labeled marker for debugging tools.
+    ///
+    /// The color is given as `0xRRGGBBAA`.
+    /// Backends without support for debug annotations ignore this, which is also
+    /// what the default implementation does.
+    unsafe fn insert_debug_marker(&mut self, _name: &str, _color: u32) {}
 }
 
 /// A trait for types that can be converted into raw command buffer.
@@ -545,3 +613,49 @@ pub trait IntoRawCommandBuffer<B: Backend, C> {
     /// Converts into raw command buffer.
     fn into_raw(self) -> B::CommandBuffer;
 }
+
+/// The blit region downsampling `level - 1` of an image into `level`.
+fn mip_blit(extent: image::Extent, level: image::Level, layers: Range<image::Layer>) -> ImageBlit {
+    ImageBlit {
+        src_subresource: image::SubresourceLayers {
+            aspects: Aspects::COLOR,
+            level: level - 1,
+            layers: layers.clone(),
+        },
+        src_bounds: image::Offset::ZERO.into_bounds(&extent.at_level(level - 1)),
+        dst_subresource: image::SubresourceLayers {
+            aspects: Aspects::COLOR,
+            level,
+            layers,
+        },
+        dst_bounds: image::Offset::ZERO.into_bounds(&extent.at_level(level)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::mip_blit;
+    use crate::image::{Extent, Offset};
+
+    #[test]
+    fn test_mip_blit() {
+        let extent = Extent {
+            width: 16,
+            height: 4,
+            depth: 1,
+        };
+
+        let blit = mip_blit(extent, 1, 0..2);
+        assert_eq!(blit.src_subresource.level, 0);
+        assert_eq!(blit.dst_subresource.level, 1);
+        assert_eq!(blit.src_subresource.layers, 0..2);
+        assert_eq!(blit.dst_subresource.layers, 0..2);
+        assert_eq!(blit.src_bounds, Offset::ZERO..Offset { x: 16, y: 4, z: 1 });
+        assert_eq!(blit.dst_bounds, Offset::ZERO..Offset { x: 8, y: 2, z: 1 });
+
+        // Smaller dimensions stop at a single texel.
+        let blit = mip_blit(extent, 4, 0..1);
+        assert_eq!(blit.src_bounds, Offset::ZERO..Offset { x: 2, y: 1, z: 1 });
+        assert_eq!(blit.dst_bounds, Offset::ZERO..Offset { x: 1, y: 1, z: 1 });
+    }
+}
@@ -216,6 +216,21 @@ impl<B: Backend, C: BorrowMut<B::CommandBuffer>> RenderSubpassCommon<B, C> {
     pub unsafe fn write_timestamp(&mut self, stage: pso::PipelineStage, query: query::Query<B>) {
         self.cmb.borrow_mut().write_timestamp(stage, query)
     }
+
+    ///
+    pub unsafe fn begin_debug_marker(&mut self, name: &str, color: u32) {
+        self.cmb.borrow_mut().begin_debug_marker(name, color)
+    }
+
+    ///
+    pub unsafe fn end_debug_marker(&mut self) {
+        self.cmb.borrow_mut().end_debug_marker()
+    }
+
+    ///
+    pub unsafe fn insert_debug_marker(&mut self, name: &str, color: u32) {
+        self.cmb.borrow_mut().insert_debug_marker(name, color)
+    }
 }
 
 /// An object that records commands into a command buffer inline, that is,
@@ -88,6 +88,10 @@ pub enum AllocationError {
     /// Vulkan implementation doesn't allow to create too many objects.
     #[fail(display = "Can't allocate more memory objects")]
     TooManyObjects,
+
+    /// The object requires a feature that the device doesn't support.
+    #[fail(display = "Required feature is not supported")]
+    MissingFeature,
 }
 
 impl From<OutOfMemory> for AllocationError {
@@ -796,4 +800,41 @@ pub trait Device<B: Backend>: fmt::Debug + Any + Send + Sync {
     ///
     /// Host access to all queues needs to be **externally** sycnhronized!
     fn wait_idle(&self) -> Result<(), HostExecutionError>;
+
+    /// Associate a name with an image, for easier debugging in external tools or with
+    /// validation layers that can print a friendly name when referring to objects in
+    /// error messages.
+    ///
+    /// Backends without support for debug names ignore this, which is also what the
+    /// default implementation does.
+    unsafe fn set_image_name(&self, _image: &mut B::Image, _name: &str) {}
+
+    /// Associate a name with a buffer, see `set_image_name`.
+    unsafe fn set_buffer_name(&self, _buffer: &mut B::Buffer, _name: &str) {}
+
+    /// Associate a name with a command buffer, see `set_image_name`.
+    unsafe fn set_command_buffer_name(&self, _command_buffer: &mut B::CommandBuffer, _name: &str) {}
+
+    /// Associate a name with a semaphore, see `set_image_name`.
+    unsafe fn set_semaphore_name(&self, _semaphore: &mut B::Semaphore, _name: &str) {}
+
+    /// Associate a name with a fence, see `set_image_name`.
+    unsafe fn set_fence_name(&self, _fence: &mut B::Fence, _name: &str) {}
+
+    /// Associate a name with a framebuffer, see `set_image_name`.
+    unsafe fn set_framebuffer_name(&self, _framebuffer: &mut B::Framebuffer, _name: &str) {}
+
+    /// Associate a name with a render pass, see `set_image_name`.
+    unsafe fn set_render_pass_name(&self, _render_pass: &mut B::RenderPass, _name: &str) {}
+
+    /// Associate a name with a descriptor set, see `set_image_name`.
+    unsafe fn set_descriptor_set_name(&self, _descriptor_set: &mut B::DescriptorSet, _name: &str) {}
+
+    /// Associate a name with a descriptor set layout, see `set_image_name`.
+    unsafe fn set_descriptor_set_layout_name(
+        &self,
+        _descriptor_set_layout: &mut B::DescriptorSetLayout,
+        _name: &str,
+    ) {
+    }
 }
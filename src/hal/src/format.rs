@@ -209,6 +209,33 @@ pub enum ChannelType {
     Srgb,
 }
 
+impl ChannelType {
+    /// Check if the channel values are normalized to the `[0, 1]` or `[-1, 1]` range
+    /// when read in a shader.
+    pub fn is_normalized(self) -> bool {
+        match self {
+            ChannelType::Unorm | ChannelType::Snorm | ChannelType::Srgb => true,
+            _ => false,
+        }
+    }
+
+    /// Check if the channel values are floating-point.
+    pub fn is_float(self) -> bool {
+        match self {
+            ChannelType::Ufloat | ChannelType::Sfloat => true,
+            _ => false,
+        }
+    }
+
+    /// Check if the channel values are read as integers in a shader.
+    pub fn is_integer(self) -> bool {
+        match self {
+            ChannelType::Uint | ChannelType::Sint => true,
+            _ => false,
+        }
+    }
+}
+
 macro_rules! surface_types {
     { $($name:ident { $total:expr, $($aspect:ident)|*, $dim:expr $( ,$component:ident : $bits:expr )*} ,)* } => {
         /// Type of the allocated texture surface. It is supposed to only
@@ -319,6 +346,23 @@ surface_types! {
     ASTC_12x12          { 128, COLOR, (12, 12) },
 }
 
+impl SurfaceType {
+    /// Return the number of components stored per texel.
+    ///
+    /// Depth and stencil aspects are counted as one component each.
+    pub fn components(&self) -> u8 {
+        use self::SurfaceType::*;
+        match *self {
+            R8 | R16 | R32 | R64 | D16 | X8D24 | D32 | S8 | BC4 | EAC_R11 => 1,
+            R4_G4 | R8_G8 | R16_G16 | R32_G32 | R64_G64 | D16_S8 | D24_S8 | D32_S8 | BC5
+            | EAC_R11_G11 => 2,
+            R5_G6_B5 | B5_G6_R5 | R8_G8_B8 | B8_G8_R8 | R16_G16_B16 | R32_G32_B32 | R64_G64_B64
+            | B10_G11_R11 | E5_B9_G9_R9 | BC1_RGB | BC6 | ETC2_R8_G8_B8 => 3,
+            _ => 4,
+        }
+    }
+}
+
 /// Generic run-time base format.
 #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -592,6 +636,16 @@ impl Format {
         self.base_format().0.desc()
     }
 
+    /// Returns if the format is block-compressed.
+    pub fn is_compressed(self) -> bool {
+        self.surface_desc().is_compressed()
+    }
+
+    /// Returns if the format is sRGB non-linear encoded.
+    pub fn is_srgb(self) -> bool {
+        self.base_format().1 == ChannelType::Srgb
+    }
+
     /// Returns if the format has a color aspect.
     pub fn is_color(self) -> bool {
         self.surface_desc().aspects.contains(Aspects::COLOR)
@@ -621,3 +675,54 @@ impl AsFormat for [f32; 3] {
 impl AsFormat for [f32; 4] {
     const SELF: Format = Format::Rgba32Sfloat;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{ChannelType, Format, SurfaceType};
+
+    #[test]
+    fn test_channel_type() {
+        let rgba8_srgb = Format::Rgba8Srgb.base_format().1;
+        assert!(rgba8_srgb.is_normalized());
+        assert!(!rgba8_srgb.is_float());
+        assert!(!rgba8_srgb.is_integer());
+
+        let r32_sfloat = Format::R32Sfloat.base_format().1;
+        assert!(!r32_sfloat.is_normalized());
+        assert!(r32_sfloat.is_float());
+        assert!(!r32_sfloat.is_integer());
+
+        let rg16_uint = Format::Rg16Uint.base_format().1;
+        assert!(!rg16_uint.is_normalized());
+        assert!(!rg16_uint.is_float());
+        assert!(rg16_uint.is_integer());
+
+        assert!(!ChannelType::Uscaled.is_normalized());
+        assert!(!ChannelType::Uscaled.is_float());
+        assert!(!ChannelType::Uscaled.is_integer());
+    }
+
+    #[test]
+    fn test_surface_components() {
+        assert_eq!(Format::Rgba8Srgb.base_format().0.components(), 4);
+        assert_eq!(Format::Bc1RgbUnorm.base_format().0.components(), 3);
+        assert_eq!(Format::R32Sfloat.base_format().0.components(), 1);
+        assert_eq!(Format::Rg16Uint.base_format().0.components(), 2);
+        assert_eq!(Format::D24UnormS8Uint.base_format().0.components(), 2);
+        assert_eq!(SurfaceType::D32.components(), 1);
+    }
+
+    #[test]
+    fn test_format_queries() {
+        assert!(!Format::Rgba8Srgb.is_compressed());
+        assert!(Format::Rgba8Srgb.is_srgb());
+        assert!(Format::Bc1RgbUnorm.is_compressed());
+        assert!(!Format::Bc1RgbUnorm.is_srgb());
+        assert!(!Format::R32Sfloat.is_compressed());
+        assert!(!Format::R32Sfloat.is_srgb());
+        assert!(!Format::Rg16Uint.is_compressed());
+        assert!(!Format::Rg16Uint.is_srgb());
+        assert!(!Format::D24UnormS8Uint.is_compressed());
+        assert!(!Format::D24UnormS8Uint.is_srgb());
+    }
+}
@@ -123,6 +123,9 @@ pub enum CreationError {
     /// Unsupported size in one of the dimensions.
     #[fail(display = "Unsupported size ({}) in one of the dimensions", _0)]
     Size(Size),
+    /// The number of mip levels exceeds the full chain of the image kind.
+    #[fail(display = "Unsupported number of mip levels ({})", _0)]
+    Levels(Level),
     /// The given data has a different size than the target image slice.
     #[fail(
         display = "The given data has a different size ({}) than the target image slice",
@@ -370,6 +370,9 @@ pub struct Limits {
     pub optimal_buffer_copy_pitch_alignment: buffer::Offset,
     /// Size and alignment in bytes that bounds concurrent access to host-mapped device memory.
     pub non_coherent_atom_size: usize,
+    /// Number of nanoseconds it takes for a timestamp query value to be incremented by 1.
+    /// Zero if timestamp queries are not supported.
+    pub timestamp_period: f32,
 
     /// The alignment of the vertex buffer stride.
     pub min_vertex_input_binding_stride_alignment: buffer::Offset,
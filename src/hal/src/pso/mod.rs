@@ -25,6 +25,10 @@ pub enum CreationError {
     /// Shader compilation error.
     #[fail(display = "Shader compilation error: {}", _0)]
     Shader(device::ShaderError),
+    /// Dual-source blending is not supported by the device, or is used
+    /// with more than one color target.
+    #[fail(display = "Dual-source blending is unsupported or used with several color targets")]
+    DualSourceBlending,
 
     /// Out of either host or device memory.
     #[fail(display = "{}", _0)]
@@ -82,6 +82,19 @@ pub enum Factor {
     OneMinusSrc1Alpha = 18,
 }
 
+impl Factor {
+    /// Check if the factor reads the second color output of the fragment shader.
+    pub fn is_dual_source(self) -> bool {
+        match self {
+            Factor::Src1Color
+            | Factor::OneMinusSrc1Color
+            | Factor::Src1Alpha
+            | Factor::OneMinusSrc1Alpha => true,
+            _ => false,
+        }
+    }
+}
+
 /// Blending operations.
 #[allow(missing_docs)]
 #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
@@ -123,6 +136,16 @@ impl BlendOp {
         src: Factor::One,
         dst: Factor::OneMinusSrcAlpha,
     };
+
+    /// Check if the operation uses dual-source blending factors.
+    pub fn is_dual_source(&self) -> bool {
+        match *self {
+            BlendOp::Add { src, dst }
+            | BlendOp::Sub { src, dst }
+            | BlendOp::RevSub { src, dst } => src.is_dual_source() || dst.is_dual_source(),
+            BlendOp::Min | BlendOp::Max => false,
+        }
+    }
 }
 
 /// Specifies whether to use blending, and if so,
@@ -168,6 +191,17 @@ impl BlendState {
         color: BlendOp::PREMULTIPLIED_ALPHA,
         alpha: BlendOp::PREMULTIPLIED_ALPHA,
     };
+
+    /// Check if the state uses dual-source blending factors.
+    ///
+    /// Dual-source blending requires `Features::DUAL_SRC_BLENDING` and is
+    /// limited to a single color target.
+    pub fn is_dual_source(&self) -> bool {
+        match *self {
+            BlendState::On { color, alpha } => color.is_dual_source() || alpha.is_dual_source(),
+            BlendState::Off => false,
+        }
+    }
 }
 
 impl Default for BlendState {
@@ -272,6 +306,20 @@ pub struct StencilFace {
     pub reference: State<StencilValue>,
 }
 
+impl StencilFace {
+    /// Always pass the stencil test and write the reference value,
+    /// which is set dynamically. Useful for masking areas of the target.
+    pub const REPLACE_ALWAYS: Self = StencilFace {
+        fun: Comparison::Always,
+        mask_read: State::Static(!0),
+        mask_write: State::Static(!0),
+        op_fail: StencilOp::Keep,
+        op_depth_fail: StencilOp::Keep,
+        op_pass: StencilOp::Replace,
+        reference: State::Dynamic,
+    };
+}
+
 impl Default for StencilFace {
     fn default() -> StencilFace {
         StencilFace {
@@ -332,3 +380,56 @@ bitflags!(
         const BACK = 0x2;
     }
 );
+
+#[cfg(test)]
+mod tests {
+    use super::{BlendOp, BlendState, Comparison, Factor, State, StencilFace, StencilOp};
+
+    #[test]
+    fn test_dual_source() {
+        let dual = [
+            Factor::Src1Color,
+            Factor::OneMinusSrc1Color,
+            Factor::Src1Alpha,
+            Factor::OneMinusSrc1Alpha,
+        ];
+        for &factor in &dual {
+            let src = BlendOp::Add {
+                src: factor,
+                dst: Factor::Zero,
+            };
+            let dst = BlendOp::Sub {
+                src: Factor::One,
+                dst: factor,
+            };
+            assert!(src.is_dual_source());
+            assert!(dst.is_dual_source());
+            assert!(BlendState::On {
+                color: BlendOp::ADD,
+                alpha: src,
+            }
+            .is_dual_source());
+            assert!(BlendState::On {
+                color: dst,
+                alpha: BlendOp::ADD,
+            }
+            .is_dual_source());
+        }
+
+        assert!(!BlendOp::Min.is_dual_source());
+        assert!(!BlendState::ALPHA.is_dual_source());
+        assert!(!BlendState::Off.is_dual_source());
+    }
+
+    #[test]
+    fn test_stencil_replace_always() {
+        let face = StencilFace::REPLACE_ALWAYS;
+        assert_eq!(face.fun, Comparison::Always);
+        assert_eq!(face.op_fail, StencilOp::Keep);
+        assert_eq!(face.op_depth_fail, StencilOp::Keep);
+        assert_eq!(face.op_pass, StencilOp::Replace);
+        assert_eq!(face.mask_read, State::Static(!0));
+        assert_eq!(face.mask_write, State::Static(!0));
+        assert_eq!(face.reference, State::Dynamic);
+    }
+}